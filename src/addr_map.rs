@@ -0,0 +1,236 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{eyre, Result, WrapErr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    net::{SocketAddr, TcpListener, UdpSocket},
+    path::Path,
+};
+use tracing::warn;
+
+/// Addresses a node is told to bind to and to advertise.
+#[derive(Debug)]
+pub(crate) struct NodeAddrs {
+    pub(crate) local: SocketAddr,
+    pub(crate) public: Option<SocketAddr>,
+}
+
+/// Read a mapping of node index (genesis being #1) to the addresses that node should use.
+///
+/// Each non-empty line is `<index> <local addr> [<public addr>]`, and `#` starts a comment, e.g.
+///
+/// ```text
+/// # genesis binds locally, but is advertised on the load balancer's address
+/// 1 127.0.0.1:12000 10.0.0.1:12000
+/// 2 127.0.0.2:0
+/// ```
+pub(crate) fn read(path: &Path) -> Result<BTreeMap<usize, NodeAddrs>> {
+    let content = fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not read address map {}", path.display()))?;
+
+    let mut map = BTreeMap::new();
+    for (line_idx, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (idx, addrs) = parse_line(line).wrap_err_with(|| {
+            format!(
+                "Invalid entry at {}:{}: '{}'",
+                path.display(),
+                line_idx + 1,
+                line
+            )
+        })?;
+        if map.insert(idx, addrs).is_some() {
+            return Err(eyre!(
+                "Node #{} is mapped more than once in {}",
+                idx,
+                path.display()
+            ));
+        }
+    }
+
+    Ok(map)
+}
+
+fn parse_line(line: &str) -> Result<(usize, NodeAddrs)> {
+    let mut fields = line.split_whitespace();
+    let idx: usize = fields
+        .next()
+        .unwrap_or_default()
+        .parse()
+        .wrap_err("Expected a node index")?;
+    if idx == 0 {
+        return Err(eyre!("Node indices start at 1 (genesis)"));
+    }
+
+    let local = fields
+        .next()
+        .ok_or_else(|| eyre!("Expected a local address"))?
+        .parse()
+        .wrap_err("Invalid local address")?;
+    let public = fields
+        .next()
+        .map(|addr| addr.parse().wrap_err("Invalid public address"))
+        .transpose()?;
+    if fields.next().is_some() {
+        return Err(eyre!("Expected at most an index and two addresses"));
+    }
+
+    Ok((idx, NodeAddrs { local, public }))
+}
+
+/// Check that the fixed (non-zero) local ports in `map` are free before launching any node.
+///
+/// A taken port fails the launch, unless `search` is given, in which case up to that many of the
+/// following ports are tried instead. Ports picked for other nodes are skipped, and a public
+/// address on the same port as the local one is moved along with it.
+pub(crate) fn ensure_free_ports(
+    map: &mut BTreeMap<usize, NodeAddrs>,
+    search: Option<u16>,
+) -> Result<()> {
+    let mut picked = BTreeSet::new();
+    for (idx, addrs) in map.iter_mut() {
+        let requested = addrs.local;
+        if requested.port() == 0 {
+            continue;
+        }
+
+        let mut local = requested;
+        let mut tries = 0;
+        while picked.contains(&local) || is_taken(local) {
+            tries += 1;
+            match local.port().checked_add(1) {
+                Some(port) if tries <= search.unwrap_or(0) => local.set_port(port),
+                _ if search.is_some() => {
+                    return Err(eyre!(
+                        "No free port found for node #{} within {} ports after {}",
+                        idx,
+                        tries - 1,
+                        requested
+                    ))
+                }
+                _ => {
+                    return Err(eyre!(
+                        "The local address {} of node #{} is already in use",
+                        requested,
+                        idx
+                    ))
+                }
+            }
+        }
+
+        if local != requested {
+            warn!(
+                "The local address {} of node #{} is in use, using {} instead",
+                requested, idx, local
+            );
+            if let Some(public) = addrs.public.as_mut() {
+                if public.port() == requested.port() {
+                    public.set_port(local.port());
+                }
+            }
+            addrs.local = local;
+        }
+        let _ = picked.insert(local);
+    }
+
+    Ok(())
+}
+
+/// Whether something is already bound to `addr`, over either UDP (QUIC) or TCP.
+///
+/// Only an address in use counts, e.g. an address which isn't local is left for the node to report.
+fn is_taken(addr: SocketAddr) -> bool {
+    in_use(UdpSocket::bind(addr).map(drop)) || in_use(TcpListener::bind(addr).map(drop))
+}
+
+fn in_use(bind_result: io::Result<()>) -> bool {
+    match bind_result {
+        Err(error) => error.kind() == io::ErrorKind::AddrInUse,
+        Ok(()) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The address map in a file with `contents`, written for the test `name`.
+    fn read_map(name: &str, contents: &str) -> Result<BTreeMap<usize, NodeAddrs>> {
+        let path = std::env::temp_dir().join(format!(
+            "sn_launch_tool-addr-map-{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, contents)?;
+        let map = read(&path);
+        fs::remove_file(&path)?;
+        map
+    }
+
+    #[test]
+    fn entries_are_read_skipping_comments_and_blank_lines() -> Result<()> {
+        let map = read_map(
+            "entries",
+            "# genesis\n\
+             1 127.0.0.1:12000 10.0.0.1:12000 # behind the load balancer\n\
+             \n\
+             \t3   127.0.0.2:0\n",
+        )?;
+
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(map[&1].local, "127.0.0.1:12000".parse()?);
+        assert_eq!(map[&1].public, Some("10.0.0.1:12000".parse()?));
+        assert_eq!(map[&3].local, "127.0.0.2:0".parse()?);
+        assert_eq!(map[&3].public, None);
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_addresses_are_read() -> Result<()> {
+        let map = read_map("ipv6", "1 [::1]:12000 [2001:db8::1]:12000\n2 [::]:0\n")?;
+
+        assert_eq!(map[&1].local, "[::1]:12000".parse()?);
+        assert_eq!(map[&1].public, Some("[2001:db8::1]:12000".parse()?));
+        assert_eq!(map[&2].local, "[::]:0".parse()?);
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_lines_are_rejected_with_their_line_number() {
+        for (line, reason) in [
+            ("x 127.0.0.1:0", "Expected a node index"),
+            ("0 127.0.0.1:0", "Node indices start at 1"),
+            ("2", "Expected a local address"),
+            ("2 127.0.0.1", "Invalid local address"),
+            ("2 ::1:12000", "Invalid local address"),
+            ("2 127.0.0.1:0 10.0.0.1", "Invalid public address"),
+            (
+                "2 127.0.0.1:0 10.0.0.1:0 10.0.0.2:0",
+                "at most an index and two",
+            ),
+        ] {
+            let error = read_map("malformed", &format!("1 127.0.0.1:0\n{line}\n")).unwrap_err();
+            let error = format!("{error:#}");
+            assert!(error.contains(":2: '"), "{}", error);
+            assert!(error.contains(reason), "{}", error);
+        }
+    }
+
+    #[test]
+    fn nodes_mapped_more_than_once_are_rejected() {
+        let error = read_map("duplicate", "2 127.0.0.1:0\n1 127.0.0.1:0\n2 [::1]:0\n").unwrap_err();
+        assert!(format!("{error:#}").contains("Node #2 is mapped more than once"));
+    }
+}
@@ -0,0 +1,216 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{eyre, Result, WrapErr};
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::ChildStdout,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::trace;
+
+use crate::logs;
+
+/// How often the node's log files are re-read while waiting for its address to show up.
+const ADDRESS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Marker of the line where a node logs the address it's listening on.
+const CONNECTION_INFO_MARKER: &str = "connection info";
+
+/// Wait up to `timeout` for the node logging into `node_dir` to report the address it's bound to.
+///
+/// Nodes bind to ephemeral ports, so the only way to learn the actual address is reading it back
+/// from the node's logs once it has been written. Returns `None` if it didn't appear in time.
+pub(crate) fn discover_node_addr(node_dir: &Path, timeout: Duration) -> Result<Option<SocketAddr>> {
+    discover_node_addr_since(node_dir, &BTreeMap::new(), timeout)
+}
+
+/// The length of each of the node's log files in `node_dir`, to only look for its address in what
+/// it logs afterwards with [`discover_node_addr_since`], e.g. once it's restarted.
+pub(crate) fn log_lengths(node_dir: &Path) -> BTreeMap<PathBuf, u64> {
+    fs::read_dir(node_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(logs::NODE_LOG_PREFIX)
+        })
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.len())))
+        .collect()
+}
+
+/// Like [`discover_node_addr`], but ignoring what the node had logged when its `log_lengths` were
+/// taken.
+pub(crate) fn discover_node_addr_since(
+    node_dir: &Path,
+    logged_before: &BTreeMap<PathBuf, u64>,
+    timeout: Duration,
+) -> Result<Option<SocketAddr>> {
+    let started = Instant::now();
+    loop {
+        if let Some(addr) = read_node_addr(node_dir, logged_before)? {
+            return Ok(Some(addr));
+        }
+
+        if started.elapsed() >= timeout {
+            return Ok(None);
+        }
+
+        thread::sleep(ADDRESS_POLL_INTERVAL);
+    }
+}
+
+/// Pass the lines of a node's piped `stdout` on to our stdout, or to our stderr with `to_stderr`,
+/// until it's closed, sending the address of the first connection info line down the returned
+/// channel as soon as it's seen.
+pub(crate) fn scan_stdout(stdout: ChildStdout, to_stderr: bool) -> Receiver<SocketAddr> {
+    let (sender, receiver) = mpsc::channel();
+    let _ = thread::spawn(move || {
+        let mut stdout = BufReader::new(stdout);
+        let mut line = vec![];
+        let mut sender = Some(sender);
+        // whether the previous line had the marker without the address, which may follow it
+        let mut marker_seen = false;
+        while matches!(stdout.read_until(b'\n', &mut line), Ok(read) if read > 0) {
+            let _ = if to_stderr {
+                io::stderr().write_all(&line)
+            } else {
+                io::stdout().write_all(&line)
+            };
+
+            if sender.is_some() {
+                let text = String::from_utf8_lossy(&line);
+                let addr = if marker_seen {
+                    marker_seen = false;
+                    first_socket_addr(&text)
+                } else {
+                    None
+                };
+                let addr = addr.or_else(|| {
+                    let pos = text.find(CONNECTION_INFO_MARKER)?;
+                    let addr = first_socket_addr(&text[pos + CONNECTION_INFO_MARKER.len()..]);
+                    marker_seen = addr.is_none();
+                    addr
+                });
+                if let Some(addr) = addr {
+                    trace!("Found node address {} in its output", addr);
+                    if let Some(sender) = sender.take() {
+                        let _ = sender.send(addr);
+                    }
+                }
+            }
+            line.clear();
+        }
+    });
+    receiver
+}
+
+/// `ip` with `port`, bracketing the IP if it's an IPv6 one, e.g. `[::1]:0`.
+pub(crate) fn with_port(ip: &str, port: u16) -> String {
+    if ip.contains(':') && !ip.starts_with('[') {
+        format!("[{ip}]:{port}")
+    } else {
+        format!("{ip}:{port}")
+    }
+}
+
+/// Fail, listing the nodes involved, if any two of the named `nodes` have the same address.
+pub(crate) fn ensure_distinct<'a>(
+    nodes: impl IntoIterator<Item = (&'a str, SocketAddr)>,
+) -> Result<()> {
+    let mut by_addr: BTreeMap<SocketAddr, Vec<&str>> = BTreeMap::new();
+    for (name, addr) in nodes {
+        by_addr.entry(addr).or_default().push(name);
+    }
+
+    let duplicates: Vec<_> = by_addr
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|(addr, names)| format!("{} is used by {}", addr, names.join(", ")))
+        .collect();
+    if duplicates.is_empty() {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "Nodes are listening on the same address:\n{}",
+            duplicates.join("\n")
+        ))
+    }
+}
+
+fn read_node_addr(
+    node_dir: &Path,
+    logged_before: &BTreeMap<PathBuf, u64>,
+) -> Result<Option<SocketAddr>> {
+    let entries = match fs::read_dir(node_dir) {
+        Ok(entries) => entries,
+        // the node may not have created its directory yet
+        Err(_) => return Ok(None),
+    };
+
+    for entry in entries {
+        let entry = entry.wrap_err("Error reading node log dir")?;
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(logs::NODE_LOG_PREFIX)
+        {
+            continue;
+        }
+
+        trace!("Looking for node address in {}", entry.path().display());
+        let log = fs::read(entry.path())
+            .wrap_err_with(|| format!("Could not read node log {}", entry.path().display()))?;
+        // a log which got shorter since was rotated, and is new as a whole
+        let new_from = logged_before
+            .get(&entry.path())
+            .and_then(|&len| usize::try_from(len).ok())
+            .filter(|&len| len <= log.len())
+            .unwrap_or(0);
+        if let Some(addr) = parse_connection_info(&String::from_utf8_lossy(&log[new_from..])) {
+            return Ok(Some(addr));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Find the address following the connection info marker, either on the same line or the next.
+fn parse_connection_info(log: &str) -> Option<SocketAddr> {
+    let mut lines = log.lines();
+    while let Some(line) = lines.next() {
+        if let Some(pos) = line.find(CONNECTION_INFO_MARKER) {
+            let rest = &line[pos + CONNECTION_INFO_MARKER.len()..];
+            if let Some(addr) = first_socket_addr(rest) {
+                return Some(addr);
+            }
+            if let Some(addr) = lines.next().and_then(first_socket_addr) {
+                return Some(addr);
+            }
+        }
+    }
+
+    None
+}
+
+fn first_socket_addr(text: &str) -> Option<SocketAddr> {
+    text.split(|c: char| c.is_whitespace() || c == '"' || c == ',')
+        .map(|word| word.trim_start_matches(':'))
+        .find_map(|word| word.parse().ok())
+}
@@ -0,0 +1,155 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{cmd::NodeCmd, intervals::SplitMix64};
+use eyre::{eyre, Result};
+use std::{collections::BTreeMap, fmt, ops::RangeInclusive};
+use tracing::{debug, info};
+
+/// Explicit storage limits, in bytes, for the nodes being launched, in index order.
+#[derive(Clone, Debug)]
+pub(crate) struct CapacityList(Vec<u64>);
+
+impl CapacityList {
+    /// Parse a comma separated list of sizes, e.g. `1GB,1GB,500MB`.
+    pub(crate) fn parse(spec: &str) -> Result<Self, String> {
+        spec.split(',')
+            .map(parse_size)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// A distribution to sample the nodes' storage limits from.
+#[derive(Clone, Debug)]
+pub(crate) enum CapacityDist {
+    /// Uniformly distributed between the two sizes, in bytes, inclusive.
+    Uniform { min: u64, max: u64 },
+}
+
+impl CapacityDist {
+    /// Parse a distribution, e.g. `uniform:500MB-2GB`.
+    pub(crate) fn parse(spec: &str) -> Result<Self, String> {
+        let (kind, params) = spec
+            .split_once(':')
+            .ok_or_else(|| format!("'{spec}' is not a distribution, e.g. `uniform:500MB-2GB`"))?;
+        match kind {
+            "uniform" => {
+                let (min, max) = params.split_once('-').ok_or_else(|| {
+                    format!("'{params}' is not a range of sizes, e.g. `500MB-2GB`")
+                })?;
+                let (min, max) = (parse_size(min)?, parse_size(max)?);
+                if min > max {
+                    return Err(format!(
+                        "The range '{params}' must be from the smaller to the larger size"
+                    ));
+                }
+                Ok(Self::Uniform { min, max })
+            }
+            _ => Err(format!(
+                "Unknown distribution '{kind}', the supported one is `uniform`"
+            )),
+        }
+    }
+
+    fn sample(&self, rng: &mut SplitMix64) -> u64 {
+        match *self {
+            Self::Uniform { min, max } => match (max - min).checked_add(1) {
+                Some(span) => min + rng.next() % span,
+                None => rng.next(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for CapacityDist {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Uniform { min, max } => write!(f, "uniform:{min}-{max} bytes"),
+        }
+    }
+}
+
+/// The storage limit of each of the nodes `ids`: from `list`, which must have one per node, or
+/// sampled from `dist` with a generator seeded with `seed`.
+pub(crate) fn assign(
+    list: Option<&CapacityList>,
+    dist: Option<&CapacityDist>,
+    ids: RangeInclusive<usize>,
+    seed: u64,
+) -> Result<BTreeMap<usize, u64>> {
+    let capacities: BTreeMap<_, _> = match (list, dist) {
+        (Some(CapacityList(list)), _) => {
+            let count = ids.clone().count();
+            if list.len() != count {
+                return Err(eyre!(
+                    "--capacities lists {} sizes, but {} nodes are being launched",
+                    list.len(),
+                    count
+                ));
+            }
+            ids.zip(list.iter().copied()).collect()
+        }
+        (None, Some(dist)) => {
+            info!(
+                "Sampling the nodes' capacities from {} with seed {} (pass --seed {} to reproduce them)",
+                dist, seed, seed
+            );
+            let mut rng = SplitMix64(seed);
+            ids.map(|idx| (idx, dist.sample(&mut rng))).collect()
+        }
+        (None, None) => BTreeMap::new(),
+    };
+
+    for (idx, capacity) in &capacities {
+        debug!("Node #{} will have a capacity of {} bytes", idx, capacity);
+    }
+    Ok(capacities)
+}
+
+/// `node_cmd` with the storage limit `capacity` passed as `flag`, if there's one.
+pub(crate) fn with_capacity<'a>(
+    node_cmd: &NodeCmd<'a>,
+    capacity: Option<&u64>,
+    flag: &'a str,
+) -> NodeCmd<'a> {
+    let mut cmd = node_cmd.clone();
+    if let Some(capacity) = capacity {
+        cmd.push_arg(flag);
+        cmd.push_arg(capacity.to_string());
+    }
+    cmd
+}
+
+/// Parse a size in bytes, optionally with a decimal (`KB`, `MB`, `GB`, `TB`) or binary (`KiB`,
+/// `MiB`, `GiB`, `TiB`) unit, e.g. `500MB`.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return Err(format!("'{value}' has an unknown size unit '{unit}'")),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .ok_or_else(|| format!("'{value}' is not a valid size, e.g. `500MB`"))
+}
@@ -0,0 +1,30 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often a wait for the next node checks whether it was interrupted.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Sleep for `duration`, unless `stop` is raised first. Returns whether it was.
+pub(crate) fn sleep_unless_stopped(duration: Duration, stop: &AtomicBool) -> bool {
+    let started = Instant::now();
+    while !stop.load(Ordering::SeqCst) {
+        let elapsed = started.elapsed();
+        if elapsed >= duration {
+            return false;
+        }
+        thread::sleep(STOP_POLL_INTERVAL.min(duration - elapsed));
+    }
+    true
+}
@@ -1,24 +1,104 @@
 use eyre::{eyre, Result, WrapErr};
+use regex::Regex;
 use std::{
     borrow::Cow,
+    collections::BTreeSet,
     ffi::{OsStr, OsString},
-    fmt,
-    path::Path,
-    process::{Command, Stdio},
+    fmt, io,
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, OnceLock,
+    },
     thread,
     time::Duration,
 };
-use tracing::{debug, trace};
+use tracing::{debug, info, trace, warn};
 
-const NODE_LIVENESS_TIMEOUT: Duration = Duration::from_secs(2);
+use crate::{
+    install::{self, InstallMode},
+    interrupt, logs, probe, process,
+    sanitizer::Sanitizer,
+    script,
+};
+
+/// Default time to wait after spawning a node before checking it hasn't exited early.
+pub(crate) const DEFAULT_LIVENESS_TIMEOUT_MSEC: u64 = 2000;
+
+/// Flags passed to every node on top of its command's args.
+const NODE_DIR_FLAGS: [&str; 2] = ["--root-dir", "--log-dir"];
+
+/// Dir within each node's dir used as its bootstrap cache with [`NodeCmd::set_fresh_bootstrap_cache`].
+const BOOTSTRAP_CACHE_DIR: &str = "bootstrap_cache";
+
+/// Files in each node's dir its stdout and stderr are written to with [`NodeCmd::set_detach`] or
+/// [`NodeCmd::set_output_to_files`].
+const STDOUT_FILE: &str = "stdout.log";
+const STDERR_FILE: &str = "stderr.log";
+
+/// Number of output files of previous runs of a node kept when it's relaunched, as `stdout.log.1`
+/// for the latest and so on.
+const KEPT_OUTPUT_FILES: usize = 3;
+
+/// File in each node's dir its env is written to with [`NodeCmd::set_record_env`].
+const ENV_FILE: &str = "env.txt";
+
+/// How much of the start of a node's log is scanned for [`NodeCmd::set_fail_log_patterns`].
+const EARLY_LOG_SCAN_BYTES: u64 = 1024 * 1024;
+
+/// Default number of times a node which fails within the liveness timeout is respawned.
+pub(crate) const DEFAULT_SPAWN_RETRIES: u32 = 2;
+
+/// Backoff before respawning a node, multiplied by the number of the attempt.
+const SPAWN_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Default amount of a node's most recent log output included when it exits early.
+pub(crate) const DEFAULT_EXIT_LOG_BYTES: u64 = 4 * 1024;
 
 #[derive(Clone)]
 pub(crate) struct NodeCmd<'a> {
     path: Cow<'a, OsStr>,
     envs: Vec<(Cow<'a, OsStr>, Cow<'a, OsStr>)>,
     args: NodeArgs<'a>,
+    // args given by the user to pass on as they are, after all the other args but the node's dirs
+    passthrough_args: NodeArgs<'a>,
     // run w/ flamegraph
     flame: bool,
+    // run w/ `cargo run` from a sn_node workspace
+    cargo_run: Option<CargoRun<'a>>,
+    // how much of the node's latest log output to report if it exits early
+    exit_log_bytes: u64,
+    // how long to wait after spawning the node to check it hasn't exited, or no check if zero
+    liveness_timeout: Duration,
+    // how many times to respawn the node if it fails within the liveness timeout
+    spawn_retries: u32,
+    // place the binary into each node's dir, rather than running it from `path`
+    install_mode: Option<InstallMode>,
+    // send the node's stdout to our stderr, leaving our stdout for machine-readable output
+    stdout_to_stderr: bool,
+    // pipe the node's stdout to us, to be read from its `Child`
+    pipe_stdout: bool,
+    // give each node an empty bootstrap cache of its own
+    fresh_bootstrap_cache: bool,
+    // run the node in the background, detached from our terminal and stdio
+    detach: bool,
+    // write the node's stdout and stderr to files in its dir rather than to ours
+    output_to_files: bool,
+    // write the env the node is run with to a file in its dir
+    record_env: bool,
+    // patterns whose appearance in the node's early log fails its launch
+    fail_log_patterns: Vec<Regex>,
+    // sanitizer the binary was built with, and the options to run it with
+    sanitizer: Option<(Sanitizer, String)>,
+    // where to record the spawned node to be killed on Ctrl-C, shared between clones
+    spawned: Option<interrupt::Spawned>,
+    // where to count the respawns of failed nodes, shared between clones
+    retries: Option<Arc<AtomicU32>>,
+    // flags listed by the binary's `--help`, only probed once and shared between clones
+    help_flags: Arc<OnceLock<BTreeSet<String>>>,
+    // version reported by the binary's `-V`, likewise
+    version: Arc<OnceLock<String>>,
 }
 
 impl<'a> NodeCmd<'a> {
@@ -32,7 +112,25 @@ impl<'a> NodeCmd<'a> {
             path: into_cow_os_str(path),
             envs: Default::default(),
             args: Default::default(),
+            passthrough_args: Default::default(),
             flame: false,
+            cargo_run: None,
+            exit_log_bytes: DEFAULT_EXIT_LOG_BYTES,
+            liveness_timeout: Duration::from_millis(DEFAULT_LIVENESS_TIMEOUT_MSEC),
+            spawn_retries: DEFAULT_SPAWN_RETRIES,
+            install_mode: None,
+            stdout_to_stderr: false,
+            pipe_stdout: false,
+            fresh_bootstrap_cache: false,
+            detach: false,
+            output_to_files: false,
+            record_env: false,
+            fail_log_patterns: vec![],
+            sanitizer: None,
+            spawned: None,
+            retries: None,
+            help_flags: Default::default(),
+            version: Default::default(),
         }
     }
 
@@ -40,6 +138,12 @@ impl<'a> NodeCmd<'a> {
         Path::new(&self.path)
     }
 
+    pub(crate) fn set_path(&mut self, path: &'a Path) {
+        self.path = path.as_os_str().into();
+        self.help_flags = Default::default();
+        self.version = Default::default();
+    }
+
     pub(crate) fn set_flame(&mut self, flame: bool) {
         self.flame = flame
     }
@@ -48,10 +152,140 @@ impl<'a> NodeCmd<'a> {
         self.flame
     }
 
-    pub(crate) fn args(&self) -> &NodeArgs {
+    pub(crate) fn set_exit_log_bytes(&mut self, exit_log_bytes: u64) {
+        self.exit_log_bytes = exit_log_bytes
+    }
+
+    /// Wait `liveness_timeout` after spawning each node to check it hasn't exited early, failing
+    /// its launch if it has. A zero timeout skips the check.
+    pub(crate) fn set_liveness_timeout(&mut self, liveness_timeout: Duration) {
+        self.liveness_timeout = liveness_timeout
+    }
+
+    /// Respawn a node which fails within the liveness timeout up to `spawn_retries` times, with a
+    /// growing backoff, before failing its launch.
+    pub(crate) fn set_spawn_retries(&mut self, spawn_retries: u32) {
+        self.spawn_retries = spawn_retries
+    }
+
+    /// Count each respawn of a node which failed in `retries`.
+    pub(crate) fn set_retry_counter(&mut self, retries: Arc<AtomicU32>) {
+        self.retries = Some(retries)
+    }
+
+    pub(crate) fn set_install_mode(&mut self, install_mode: Option<InstallMode>) {
+        self.install_mode = install_mode
+    }
+
+    pub(crate) fn set_stdout_to_stderr(&mut self, stdout_to_stderr: bool) {
+        self.stdout_to_stderr = stdout_to_stderr
+    }
+
+    /// Pipe the node's stdout to us rather than passing it on, for the caller to read it from the
+    /// spawned `Child`. Ignored when its output is written to files.
+    pub(crate) fn set_pipe_stdout(&mut self, pipe_stdout: bool) {
+        self.pipe_stdout = pipe_stdout
+    }
+
+    /// Point each node at an empty bootstrap cache in its own dir, clearing any left by a previous
+    /// run, instead of letting the nodes share the default one.
+    pub(crate) fn set_fresh_bootstrap_cache(&mut self, fresh_bootstrap_cache: bool) {
+        self.fresh_bootstrap_cache = fresh_bootstrap_cache
+    }
+
+    /// Detach the nodes from the terminal, writing their stdout and stderr to files in their
+    /// dirs, so they keep running once it's closed.
+    pub(crate) fn set_detach(&mut self, detach: bool) {
+        self.detach = detach
+    }
+
+    /// Write the nodes' stdout and stderr to files in their dirs, keeping the terminal clear.
+    pub(crate) fn set_output_to_files(&mut self, output_to_files: bool) {
+        self.output_to_files = output_to_files
+    }
+
+    /// Write the env vars set for each node, e.g. its resolved `RUST_LOG`, to `env.txt` in its dir
+    /// when it's run, one `KEY=value` per line.
+    pub(crate) fn set_record_env(&mut self, record_env: bool) {
+        self.record_env = record_env
+    }
+
+    /// Fail a node's launch if a line of its log matches any of `patterns` once it has survived
+    /// the liveness check, killing the node.
+    pub(crate) fn set_fail_log_patterns(&mut self, patterns: Vec<Regex>) {
+        self.fail_log_patterns = patterns
+    }
+
+    /// Run a binary instrumented with `sanitizer` with its runtime `options`, writing each node's
+    /// reports into its dir.
+    pub(crate) fn set_sanitizer(&mut self, sanitizer: Sanitizer, options: &str) {
+        self.sanitizer = Some((sanitizer, options.to_string()))
+    }
+
+    /// Record each spawned node in `spawned`, to be killed when the user presses Ctrl-C.
+    pub(crate) fn set_spawned(&mut self, spawned: interrupt::Spawned) {
+        self.spawned = Some(spawned)
+    }
+
+    /// The sanitizer reports the node in `node_dir` has written, if run with a sanitizer.
+    pub(crate) fn sanitizer_reports(&self, node_dir: &Path) -> Vec<PathBuf> {
+        match &self.sanitizer {
+            Some((sanitizer, _)) => sanitizer.reports(node_dir),
+            None => vec![],
+        }
+    }
+
+    /// Run the nodes with `cargo run` from the workspace at `manifest_path` (or the current
+    /// directory's) instead of a prebuilt binary.
+    pub(crate) fn set_cargo_run(
+        &mut self,
+        manifest_path: Option<&'a Path>,
+        profile: Option<&'a str>,
+    ) {
+        self.cargo_run = Some(CargoRun::new(manifest_path, profile))
+    }
+
+    /// Build the node binary once up front when using `cargo run`, so that launching the nodes
+    /// doesn't trigger concurrent builds.
+    pub(crate) fn build(&self) -> Result<()> {
+        let cargo_run = match &self.cargo_run {
+            Some(cargo_run) => cargo_run,
+            None => return Ok(()),
+        };
+
+        let args = cargo_run.args("build");
+        debug!("Building sn_node with 'cargo' args {:?}", args);
+        let status = Command::new("cargo")
+            .args(&args)
+            .status()
+            .wrap_err_with(|| format!("Failed to run 'cargo' with args '{args:?}'"))?;
+        if !status.success() {
+            return Err(eyre!("Failed to build sn_node (status: {})", status));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn args(&self) -> &NodeArgs<'_> {
         &self.args
     }
 
+    /// Pass `args` to the node as they are, after the args the tool manages but before the node's
+    /// `--root-dir` and `--log-dir`, so those can't be overridden.
+    pub(crate) fn set_passthrough_args(&mut self, args: &'a [OsString]) {
+        self.passthrough_args = NodeArgs(args.iter().map(|arg| arg.as_os_str().into()).collect());
+    }
+
+    pub(crate) fn passthrough_args(&self) -> &NodeArgs<'_> {
+        &self.passthrough_args
+    }
+
+    pub(crate) fn envs(&self) -> impl Iterator<Item = (&OsStr, &OsStr)> {
+        self.envs
+            .iter()
+            .map(|(key, value)| (key.as_ref(), value.as_ref()))
+    }
+
     pub(crate) fn push_env<K, Kb, V, Vb>(&mut self, key: K, value: V)
     where
         K: Into<Cow<'a, Kb>>,
@@ -75,8 +309,82 @@ impl<'a> NodeCmd<'a> {
     }
 
     pub(crate) fn version(&self) -> Result<String> {
-        let version = Command::new(&self.path)
-            .args(["-V"])
+        if let Some(version) = self.version.get() {
+            return Ok(version.clone());
+        }
+
+        let version = self.probe(&["-V"])?;
+        let version = String::from_utf8_lossy(&version).trim().to_string();
+
+        Ok(self.version.get_or_init(|| version).clone())
+    }
+
+    /// Whether the node binary lists `flag` among the options in its `--help` output.
+    pub(crate) fn supports_flag(&self, flag: &str) -> Result<bool> {
+        Ok(self.help_flags()?.contains(flag))
+    }
+
+    /// Fail with a descriptive error if the node binary doesn't support all the flags this command
+    /// passes to it, or any of the `extra_flags` only passed to some of the nodes.
+    pub(crate) fn check_flags(&self, extra_flags: &[&str]) -> Result<()> {
+        let supported = self.help_flags()?;
+        let unsupported: BTreeSet<_> = self
+            .args
+            .into_iter()
+            .filter_map(|arg| arg.to_str())
+            .filter(|arg| arg.starts_with("--"))
+            .chain(NODE_DIR_FLAGS)
+            .chain(extra_flags.iter().copied())
+            .filter(|flag| !supported.contains(*flag))
+            .collect();
+
+        if unsupported.is_empty() {
+            debug!("sn_node supports all the flags it will be launched with");
+            Ok(())
+        } else {
+            Err(eyre!(
+                "The sn_node binary at {} does not support {}",
+                self.path().display(),
+                unsupported.into_iter().collect::<Vec<_>>().join(", ")
+            ))
+        }
+    }
+
+    fn help_flags(&self) -> Result<&BTreeSet<String>> {
+        if let Some(flags) = self.help_flags.get() {
+            return Ok(flags);
+        }
+
+        let flags = probe::parse_help_flags(&self.probe(&["--help"])?);
+        trace!("sn_node supports flags {:?}", flags);
+
+        Ok(self.help_flags.get_or_init(|| flags))
+    }
+
+    /// Fail with a descriptive error if the node binary doesn't support `flag`.
+    pub(crate) fn ensure_supports(&self, flag: &str) -> Result<()> {
+        if self.supports_flag(flag)? {
+            Ok(())
+        } else {
+            Err(eyre!(
+                "The sn_node binary at {} does not support {}",
+                self.path().display(),
+                flag
+            ))
+        }
+    }
+
+    fn probe(&self, args: &[&str]) -> Result<Vec<u8>> {
+        let mut cmd = match &self.cargo_run {
+            Some(cargo_run) => {
+                let mut cmd = Command::new("cargo");
+                cmd.args(&cargo_run.args("run")).args(["--quiet", "--"]);
+                cmd
+            }
+            None => Command::new(&self.path),
+        };
+
+        cmd.args(args)
             .output()
             .map_or_else(
                 |error| Err(eyre!(error)),
@@ -93,100 +401,390 @@ impl<'a> NodeCmd<'a> {
                 },
             )
             .wrap_err_with(|| {
-                format!(
-                    "Failed to run '{}' with args '{:?}'",
-                    self.path().display(),
-                    &["-V"]
-                )
-            })?;
+                let program = match self.cargo_run {
+                    Some(_) => "cargo run".to_string(),
+                    None => self.path().display().to_string(),
+                };
+                format!("Failed to run '{program}' with args '{args:?}'")
+            })
+    }
+
+    /// How the node `node_name` is run: the program, its args and env, and where it's run from.
+    pub(crate) fn invocation(&self, node_name: &str, nodes_dir: &Path) -> Invocation {
+        let node_dir = nodes_dir.join(node_name);
+
+        let mut program = self.path().as_os_str().to_os_string();
+        let mut prefix_args = NodeArgs::default();
+        let mut current_dir = None;
+
+        if self.gen_flamegraph() {
+            program = "cargo".into();
+            prefix_args.push("flamegraph");
+            prefix_args.push(format!("-o {node_name}-flame.svg"));
+            prefix_args.push("--root");
+            prefix_args.push("--bin");
+            prefix_args.push("sn_node");
+            prefix_args.push("--");
+            // we set the command ro run in each individal node dir (as each flamegraph uses a file `cargo-flamegraph.stacks` which cannot be renamed per per node)
+            // we set flamegraph to root as that's necesasry on mac
+            current_dir = Some(PathBuf::from(node_name));
+        } else if let Some(cargo_run) = &self.cargo_run {
+            program = "cargo".into();
+            prefix_args = cargo_run.args("run");
+            prefix_args.push("--");
+        } else if self.install_mode.is_some() {
+            program = install::installed_path(self.path(), &node_dir).into();
+        }
 
-        Ok(String::from_utf8_lossy(&version).trim().to_string())
+        let mut extra_args = NodeArgs::default();
+        extra_args.push("--root-dir");
+        extra_args.push(node_dir.clone());
+        extra_args.push("--log-dir");
+        extra_args.push(node_dir.clone());
+        if self.fresh_bootstrap_cache {
+            extra_args.push("--bootstrap-cache");
+            extra_args.push(node_dir.join(BOOTSTRAP_CACHE_DIR));
+        }
+
+        let mut envs: Vec<_> = self
+            .envs()
+            .map(|(key, value)| (key.to_os_string(), value.to_os_string()))
+            .collect();
+        if let Some((sanitizer, options)) = &self.sanitizer {
+            envs.push((
+                sanitizer.options_var().into(),
+                sanitizer.options(&node_dir, options),
+            ));
+        }
+
+        Invocation {
+            program,
+            args: prefix_args
+                .into_iter()
+                .chain(&self.args)
+                .chain(&self.passthrough_args)
+                .chain(&extra_args)
+                .map(|arg| arg.to_os_string())
+                .collect(),
+            envs,
+            current_dir,
+        }
+    }
+
+    /// Print the shell command the node `node_name` would be run with, including its env, for
+    /// --dry-run.
+    pub(crate) fn print_invocation(&self, node_name: &str, nodes_dir: &Path) {
+        let invocation = self.invocation(node_name, nodes_dir);
+        println!("{}  # {}", script::command_line(&invocation), node_name);
     }
 
-    pub(crate) fn run(&self, node_name: &str, node_dir: &Path) -> Result<()> {
-        let node_dir = node_dir.join(node_name);
+    /// The first line of the start of the node's log matching any of the fail log patterns.
+    fn failing_log_line(&self, node_dir: &Path) -> Option<String> {
+        if self.fail_log_patterns.is_empty() {
+            return None;
+        }
+        logs::head(node_dir, EARLY_LOG_SCAN_BYTES)
+            .lines()
+            .find(|line| {
+                self.fail_log_patterns
+                    .iter()
+                    .any(|pattern| pattern.is_match(line))
+            })
+            .map(str::to_string)
+    }
 
-        let mut cmd = self.path().display().to_string();
+    /// Run the node `node_name` in `nodes_dir`, respawning it up to the spawn retries times if it
+    /// fails within the liveness timeout.
+    pub(crate) fn run(&self, node_name: &str, nodes_dir: &Path) -> Result<Child> {
+        let mut attempt = 0;
+        loop {
+            match self.spawn(node_name, nodes_dir) {
+                Err(error) if attempt < self.spawn_retries && is_failed_early_exit(&error) => {
+                    attempt += 1;
+                    if let Some(retries) = &self.retries {
+                        let _ = retries.fetch_add(1, Ordering::Relaxed);
+                    }
+                    let backoff = SPAWN_RETRY_BACKOFF * attempt;
+                    warn!(
+                        "{} failed to start, retrying in {}ms (attempt {}/{}): {}",
+                        node_name,
+                        backoff.as_millis(),
+                        attempt,
+                        self.spawn_retries,
+                        error.root_cause()
+                    );
+                    thread::sleep(backoff);
+                }
+                result => return result,
+            }
+        }
+    }
 
-        let flame_on = self.gen_flamegraph();
-        let graph_output = format!("-o {node_name}-flame.svg");
+    fn spawn(&self, node_name: &str, nodes_dir: &Path) -> Result<Child> {
+        let node_dir = nodes_dir.join(node_name);
 
-        if flame_on {
-            cmd = "cargo".to_string();
+        if self.gen_flamegraph() {
             // make a dir per node
             std::fs::create_dir_all(node_name)?;
-            debug!("Flame graph will be stored: {:?}", graph_output);
+            debug!("Flame graph will be stored: -o {node_name}-flame.svg");
+            debug!("Launching nodes via `cargo flamegraph`");
+        } else if self.cargo_run.is_some() {
+            debug!("Launching nodes via `cargo run`");
+        } else if let Some(install_mode) = self.install_mode {
+            install::install_binary(self.path(), &node_dir, install_mode)?;
         }
 
-        trace!("Running '{cmd}' with args {:?} ...", self.args);
+        if self.fresh_bootstrap_cache {
+            let cache_dir = node_dir.join(BOOTSTRAP_CACHE_DIR);
+            match std::fs::remove_dir_all(&cache_dir) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                    return Err(error).wrap_err_with(|| {
+                        format!("Could not clear bootstrap cache {}", cache_dir.display())
+                    });
+                }
+                _ => {}
+            }
+        }
 
-        let mut extra_args = NodeArgs::default();
-        extra_args.push("--root-dir");
-        extra_args.push(node_dir.clone());
-        extra_args.push("--log-dir");
-        extra_args.push(node_dir);
-
-        let mut the_cmd = Command::new(cmd.clone());
-        let additonal_flame_args = vec![
-            "flamegraph",
-            &graph_output,
-            "--root",
-            "--bin",
-            "sn_node",
-            "--",
-        ];
-        if flame_on {
-            debug!("Launching nodes via `cargo flamegraph`");
-            // we set the command ro run in each individal node dir (as each flamegraph uses a file `cargo-flamegraph.stacks` which cannot be renamed per per node)
-            // we set flamegraph to root as that's necesasry on mac
+        let invocation = self.invocation(node_name, nodes_dir);
+        debug!(
+            "Running '{}' with args {:?} ...",
+            invocation.program.to_string_lossy(),
+            invocation.args
+        );
+
+        if self.record_env {
+            write_env_file(&node_dir, &invocation.envs)?;
+        }
+
+        let mut the_cmd = Command::new(&invocation.program);
+        if let Some(current_dir) = &invocation.current_dir {
+            the_cmd.current_dir(current_dir);
+        }
+
+        if self.detach || self.output_to_files {
+            std::fs::create_dir_all(&node_dir)
+                .wrap_err_with(|| format!("Could not create directory {}", node_dir.display()))?;
+            the_cmd
+                .stdin(Stdio::null())
+                .stdout(create_output_file(&node_dir.join(STDOUT_FILE))?)
+                .stderr(create_output_file(&node_dir.join(STDERR_FILE))?);
+            if self.detach {
+                process::detach(&mut the_cmd);
+            }
+        } else {
             the_cmd
-                .current_dir(node_name)
-                .args(additonal_flame_args.clone());
+                .stdout(if self.pipe_stdout {
+                    Stdio::piped()
+                } else if self.stdout_to_stderr {
+                    Stdio::from(io::stderr())
+                } else {
+                    Stdio::inherit()
+                })
+                .stderr(Stdio::inherit());
         }
 
         the_cmd
-            .args(&self.args)
-            .args(&extra_args)
-            .envs(self.envs.iter().map(
+            .args(&invocation.args)
+            .envs(invocation.envs.iter().map(
                 // this looks like a no-op but really converts `&(_, _)` into `(_, _)`
                 |(key, value)| (key, value),
             ))
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
             .spawn()
             .map_err(|error| eyre!(error))
             .and_then(|mut child| {
-                // Wait a couple of seconds to see if the node fails immediately, so we can fail fast
-                thread::sleep(NODE_LIVENESS_TIMEOUT);
+                // Wait a moment to see if the node fails immediately, so we can fail fast
+                let exited = if self.liveness_timeout.is_zero() {
+                    None
+                } else {
+                    thread::sleep(self.liveness_timeout);
+                    child.try_wait()?
+                };
+
+                if let Some(status) = exited {
+                    let reports = self.sanitizer_reports(&node_dir);
+                    if !reports.is_empty() {
+                        warn!(
+                            "Node exited early leaving sanitizer reports: {}",
+                            display_paths(&reports)
+                        );
+                    }
+                    let log_tail = logs::tail(&node_dir, self.exit_log_bytes);
+                    let details = if log_tail.is_empty() && (self.detach || self.output_to_files) {
+                        format!(
+                            ", see {} for its output",
+                            node_dir.join(STDERR_FILE).display()
+                        )
+                    } else if log_tail.is_empty() {
+                        String::new()
+                    } else {
+                        format!(", last log output:\n{}", log_tail.trim_end())
+                    };
+                    return Err(ExitedEarly { status, details }.into());
+                }
 
-                if let Some(status) = child.try_wait()? {
-                    return Err(eyre!("Node exited early (status: {})", status));
+                if let Some(line) = self.failing_log_line(&node_dir) {
+                    let _ = child.kill().and_then(|()| child.wait());
+                    return Err(eyre!(
+                        "Node's log matches --fail-on-log-pattern: {}",
+                        line.trim()
+                    ));
                 }
 
-                Ok(())
+                // only now, as a failed node's PID may be reused by the time Ctrl-C is pressed
+                if let Some(spawned) = &self.spawned {
+                    spawned.push(child.id());
+                }
+                if let Err(error) = process::write_pid(nodes_dir, node_name, child.id()) {
+                    warn!("{:?}", error);
+                }
+
+                Ok(child)
             })
             .wrap_err_with(|| {
-                let mut all_args = vec![];
-                if flame_on {
-                    // all_args.extend(additonal_flame_args);
+                format!(
+                    "Failed to start '{}' with args '{:?}'",
+                    invocation.program.to_string_lossy(),
+                    invocation.args
+                )
+            })
+    }
+}
 
-                    for arg in additonal_flame_args {
-                        let c = into_cow_os_str(arg);
-                        all_args.push(c);
-                    }
-                }
+/// A node exiting within the liveness timeout.
+#[derive(Debug)]
+struct ExitedEarly {
+    status: ExitStatus,
+    // where to find out why, if anywhere
+    details: String,
+}
 
-                for arg in self.args.into_iter() {
-                    all_args.push(arg.clone());
-                }
-                for arg in extra_args.into_iter() {
-                    all_args.push(arg.clone());
-                }
+impl fmt::Display for ExitedEarly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Node exited early (status: {}){}",
+            self.status, self.details
+        )
+    }
+}
 
-                format!("Failed to start '{cmd}' with args '{all_args:?}'")
-            })?;
+impl std::error::Error for ExitedEarly {}
 
-        Ok(())
+/// Whether `error` is a node exiting early with a failure, e.g. having lost a race for its port,
+/// which may well not happen again.
+fn is_failed_early_exit(error: &eyre::Report) -> bool {
+    error
+        .downcast_ref::<ExitedEarly>()
+        .is_some_and(|exited| !exited.status.success())
+}
+
+/// Write `envs` to the env file in `node_dir`, one `KEY=value` per line.
+fn write_env_file(node_dir: &Path, envs: &[(OsString, OsString)]) -> Result<()> {
+    std::fs::create_dir_all(node_dir)
+        .wrap_err_with(|| format!("Could not create directory {}", node_dir.display()))?;
+    let contents: String = envs
+        .iter()
+        .map(|(key, value)| format!("{}={}\n", key.to_string_lossy(), value.to_string_lossy()))
+        .collect();
+    let path = node_dir.join(ENV_FILE);
+    std::fs::write(&path, contents)
+        .wrap_err_with(|| format!("Could not write the node's env to {}", path.display()))
+}
+
+/// `paths` as a comma separated list.
+pub(crate) fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A node's process as started by [`NodeCmd::run`].
+pub(crate) struct Invocation {
+    pub(crate) program: OsString,
+    pub(crate) args: Vec<OsString>,
+    pub(crate) envs: Vec<(OsString, OsString)>,
+    pub(crate) current_dir: Option<PathBuf>,
+}
+
+/// Whether the `cargo flamegraph` subcommand used by [`NodeCmd::set_flame`] is installed.
+pub(crate) fn flamegraph_installed() -> bool {
+    Command::new("cargo")
+        .args(["flamegraph", "--help"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Build sn_node with `cargo build` from the workspace at `manifest_path` (or the current
+/// directory's), with the cargo `profile` if given, returning the path of the built binary.
+///
+/// Cargo's output is shown as the build goes, and a failed build is an error.
+pub(crate) fn build_node_binary(
+    manifest_path: Option<&Path>,
+    profile: Option<&str>,
+) -> Result<PathBuf> {
+    let mut args = CargoRun::new(manifest_path, profile).args("build");
+    // the built artifacts are reported on stdout, while progress and diagnostics go to stderr
+    args.push("--message-format=json-render-diagnostics");
+    info!("Building sn_node with 'cargo' args {:?}", args);
+    let output = Command::new("cargo")
+        .args(&args)
+        .stderr(Stdio::inherit())
+        .output()
+        .wrap_err_with(|| format!("Failed to run 'cargo' with args '{args:?}'"))?;
+    if !output.status.success() {
+        return Err(eyre!("Failed to build sn_node (status: {})", output.status));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(artifact_executable)
+        .ok_or_else(|| eyre!("cargo didn't report building an sn_node executable"))
+}
+
+/// The `executable` of a `compiler-artifact` message from cargo's JSON output.
+fn artifact_executable(message: &str) -> Option<PathBuf> {
+    let message: serde_json::Value = serde_json::from_str(message).ok()?;
+    if message["reason"] != "compiler-artifact" {
+        return None;
+    }
+    message["executable"].as_str().map(PathBuf::from)
+}
+
+#[derive(Clone)]
+struct CargoRun<'a> {
+    manifest_path: Option<Cow<'a, OsStr>>,
+    profile: Option<&'a str>,
+}
+
+impl<'a> CargoRun<'a> {
+    fn new(manifest_path: Option<&'a Path>, profile: Option<&'a str>) -> Self {
+        Self {
+            manifest_path: manifest_path.map(|path| path.as_os_str().into()),
+            profile,
+        }
+    }
+
+    /// Args for a cargo `subcommand` targeting the sn_node binary.
+    fn args(&self, subcommand: &'a str) -> NodeArgs<'a> {
+        let mut args = NodeArgs::default();
+        args.push(subcommand);
+        if let Some(manifest_path) = &self.manifest_path {
+            args.push("--manifest-path");
+            args.push(manifest_path.clone());
+        }
+        if let Some(profile) = self.profile {
+            args.push("--profile");
+            args.push(profile);
+        }
+        args.push("--bin");
+        args.push("sn_node");
+        args
     }
 }
 
@@ -233,3 +831,28 @@ where
         Cow::Owned(val) => val.into().into(),
     }
 }
+
+/// Create the output file at `path`, first rotating the one left by a previous run of the node, if
+/// any, to `<path>.1`, and so on.
+fn create_output_file(path: &Path) -> Result<std::fs::File> {
+    let rotated = |n: usize| {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    };
+    for n in (0..KEPT_OUTPUT_FILES).rev() {
+        let from = if n == 0 {
+            path.to_path_buf()
+        } else {
+            rotated(n)
+        };
+        match std::fs::rename(&from, rotated(n + 1)) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                return Err(error).wrap_err_with(|| format!("Could not rotate {}", from.display()));
+            }
+            _ => {}
+        }
+    }
+
+    std::fs::File::create(path).wrap_err_with(|| format!("Could not create {}", path.display()))
+}
@@ -0,0 +1,146 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{
+    cmd::NodeCmd,
+    naming::{NamingArgs, GENESIS_IDX},
+};
+use eyre::{eyre, Result, WrapErr};
+use std::{fmt::Write, fs, net::Ipv4Addr, path::Path};
+use tracing::info;
+
+/// Subnet of the compose network the nodes get their static IPs from.
+const SUBNET: Ipv4Addr = Ipv4Addr::new(172, 28, 0, 0);
+const SUBNET_PREFIX_LEN: u32 = 16;
+
+/// Port every node listens on inside its container.
+const NODE_PORT: u16 = 12000;
+
+/// Where each node's volume is mounted, and the genesis' volume in the other nodes' containers.
+const NODE_DIR: &str = "/node";
+const GENESIS_DIR: &str = "/genesis";
+
+/// Write a docker-compose file describing a network of `num_nodes` nodes, each running in a
+/// container of `image` with the common args and envs of `node_cmd`.
+///
+/// Each node gets a static IP on its own compose network and a volume for its root and log dir.
+/// The genesis node's volume is also mounted read-only into the others, for its network contacts.
+pub(crate) fn write_compose_file(
+    path: &Path,
+    image: &str,
+    node_cmd: &NodeCmd,
+    naming: &NamingArgs,
+    num_nodes: usize,
+) -> Result<()> {
+    if num_nodes as u64 + 2 >= 1 << (32 - SUBNET_PREFIX_LEN) {
+        return Err(eyre!(
+            "Too many nodes for the compose network's {}/{} subnet",
+            SUBNET,
+            SUBNET_PREFIX_LEN
+        ));
+    }
+
+    let common_args: Vec<_> = node_cmd
+        .args()
+        .into_iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let passthrough_args: Vec<_> = node_cmd
+        .passthrough_args()
+        .into_iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let envs: Vec<_> = node_cmd
+        .envs()
+        .map(|(key, value)| (key.to_string_lossy(), value.to_string_lossy()))
+        .collect();
+
+    let genesis = naming.node_name(GENESIS_IDX);
+
+    let mut compose = "# Generated by sn_launch_tool\nservices:\n".to_string();
+    for idx in 1..=num_nodes {
+        let name = naming.node_name(idx);
+        let ip = node_ip(idx);
+        let addr = format!("{ip}:{NODE_PORT}");
+
+        let mut args = common_args.clone();
+        args.extend(["--local-addr".to_string(), addr.clone()]);
+        if idx == GENESIS_IDX {
+            args.extend(["--first".to_string(), addr]);
+        } else {
+            args.extend([
+                "--network-contacts-file".to_string(),
+                format!("{GENESIS_DIR}/section_tree"),
+            ]);
+        }
+        args.extend(passthrough_args.iter().cloned());
+        args.extend(["--root-dir", NODE_DIR, "--log-dir", NODE_DIR].map(str::to_string));
+
+        let _ = writeln!(compose, "  {name}:");
+        let _ = writeln!(compose, "    image: {}", quote(image));
+        let _ = writeln!(compose, "    entrypoint: [\"sn_node\"]");
+        let _ = writeln!(compose, "    command: {}", list(&args));
+        if !envs.is_empty() {
+            let _ = writeln!(compose, "    environment:");
+            for (key, value) in &envs {
+                let _ = writeln!(compose, "      {}: {}", key, quote(value));
+            }
+        }
+        let _ = writeln!(compose, "    volumes:");
+        let _ = writeln!(compose, "      - {}", quote(&format!("{name}:{NODE_DIR}")));
+        if idx != GENESIS_IDX {
+            let _ = writeln!(
+                compose,
+                "      - {}",
+                quote(&format!("{genesis}:{GENESIS_DIR}:ro"))
+            );
+            let _ = writeln!(compose, "    depends_on: [{}]", quote(&genesis));
+            // until genesis has written its network contacts
+            let _ = writeln!(compose, "    restart: on-failure");
+        }
+        let _ = writeln!(compose, "    networks:");
+        let _ = writeln!(compose, "      sn-network:");
+        let _ = writeln!(compose, "        ipv4_address: {ip}");
+    }
+
+    let _ = writeln!(compose, "networks:");
+    let _ = writeln!(compose, "  sn-network:");
+    let _ = writeln!(compose, "    ipam:");
+    let _ = writeln!(compose, "      config:");
+    let _ = writeln!(compose, "        - subnet: {SUBNET}/{SUBNET_PREFIX_LEN}");
+    let _ = writeln!(compose, "volumes:");
+    for idx in 1..=num_nodes {
+        let _ = writeln!(compose, "  {}:", naming.node_name(idx));
+    }
+
+    fs::write(path, compose)
+        .wrap_err_with(|| format!("Could not write compose file {}", path.display()))?;
+    info!(
+        "Compose file for {} nodes written to {}",
+        num_nodes,
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Static IP of the node, the subnet's first address being left for the gateway.
+fn node_ip(idx: usize) -> Ipv4Addr {
+    Ipv4Addr::from(u32::from(SUBNET) + 1 + idx as u32)
+}
+
+/// A double-quoted YAML string.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn list(values: &[String]) -> String {
+    let values: Vec<_> = values.iter().map(|value| quote(value)).collect();
+    format!("[{}]", values.join(", "))
+}
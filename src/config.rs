@@ -0,0 +1,192 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{profiles, Launch};
+use clap::{ArgAction, CommandFactory};
+use eyre::{eyre, Result, WrapErr};
+use serde::Deserialize;
+use std::{collections::BTreeMap, convert::TryFrom, ffi::OsString, fs, path::Path};
+
+/// A value in a --config file, which is passed as the value of its flag.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Value {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+/// The value of an option in a --config file: a list gives the flag once per value.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OptionValue {
+    One(Value),
+    List(Vec<Value>),
+}
+
+/// `args` with the flags of the options in the --config file given in them, if any, inserted
+/// right after the program name, so that flags given explicitly override them.
+pub(crate) fn expand(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    // config files are only for launching, not for the subcommands, which clap will report
+    if profiles::is_subcommand(&args) {
+        return Ok(args);
+    }
+
+    match profiles::flag_value(&args, "--config") {
+        Some(path) => {
+            let config_args = read(Path::new(&path))?;
+            let mut args = args.into_iter();
+            Ok(args
+                .next()
+                .into_iter()
+                .chain(config_args)
+                .chain(args)
+                .collect())
+        }
+        None => Ok(args),
+    }
+}
+
+/// The flags the options of the config file at `path` stand for, failing on the options which
+/// aren't launch options.
+fn read(path: &Path) -> Result<Vec<OsString>> {
+    let contents = fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not read config file {}", path.display()))?;
+    let options: BTreeMap<String, OptionValue> = toml::from_str(&contents)
+        .wrap_err_with(|| format!("Invalid config file {}", path.display()))?;
+
+    let command = Launch::command();
+    let mut args = vec![];
+    for (key, value) in options {
+        let long = key.replace('_', "-");
+        if long == "config" {
+            return Err(eyre!(
+                "Config file {} can't include another with `{}`",
+                path.display(),
+                key
+            ));
+        }
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+            .ok_or_else(|| eyre!("Unknown option `{}` in config file {}", key, path.display()))?;
+
+        let values = match value {
+            OptionValue::One(value) => vec![value],
+            OptionValue::List(values) => values,
+        };
+        for value in values {
+            let flag = OsString::from(format!("--{long}"));
+            if !matches!(arg.get_action(), ArgAction::SetTrue | ArgAction::Count) {
+                args.push(flag);
+                args.push(match value {
+                    Value::Bool(value) => value.to_string().into(),
+                    Value::Integer(value) => value.to_string().into(),
+                    Value::Float(value) => value.to_string().into(),
+                    Value::String(value) => value.into(),
+                });
+                continue;
+            }
+
+            // a flag without a value, set by `true`, or given a number of times, e.g. verbosity
+            let times = match value {
+                Value::Bool(value) => Some(usize::from(value)),
+                Value::Integer(times) => usize::try_from(times).ok(),
+                Value::Float(_) | Value::String(_) => None,
+            };
+            let times = times.ok_or_else(|| {
+                eyre!(
+                    "Option `{}` in config file {} takes `true` or `false`{}",
+                    key,
+                    path.display(),
+                    if matches!(arg.get_action(), ArgAction::Count) {
+                        ", or a number of times"
+                    } else {
+                        ""
+                    }
+                )
+            })?;
+            args.extend(std::iter::repeat_n(flag, times));
+        }
+    }
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The flags of a config file with `contents`, written for the test `name`.
+    fn read_config(name: &str, contents: &str) -> Result<Vec<String>> {
+        let path = std::env::temp_dir().join(format!(
+            "sn_launch_tool-config-{}-{}.toml",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, contents)?;
+        let args = read(&path);
+        fs::remove_file(&path)?;
+        Ok(args?
+            .into_iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect())
+    }
+
+    #[test]
+    fn options_are_expanded_into_their_flags() -> Result<()> {
+        let args = read_config(
+            "expand",
+            "num_nodes = 3\n\
+             nodes-dir = \"./testnet\"\n\
+             skip_version_check = true\n\
+             no_backup = false\n\
+             nodes_verbosity = 2\n\
+             ip = [\"127.0.0.1\", \"127.0.0.2\"]\n",
+        )?;
+
+        assert_eq!(
+            args,
+            [
+                "--ip",
+                "127.0.0.1",
+                "--ip",
+                "127.0.0.2",
+                "--nodes-dir",
+                "./testnet",
+                "--nodes-verbosity",
+                "--nodes-verbosity",
+                "--num-nodes",
+                "3",
+                "--skip-version-check",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_options_are_rejected() {
+        let error = read_config("unknown", "num_nodes = 3\nnot_an_option = 1\n").unwrap_err();
+        assert!(format!("{error:#}").contains("Unknown option `not_an_option`"));
+    }
+
+    #[test]
+    fn config_files_cant_include_another() {
+        let error = read_config("include", "config = \"other.toml\"\n").unwrap_err();
+        assert!(format!("{error:#}").contains("can't include another with `config`"));
+    }
+
+    #[test]
+    fn negative_counts_are_rejected() {
+        let error = read_config("negative", "nodes_verbosity = -1\n").unwrap_err();
+        assert!(format!("{error:#}").contains("Option `nodes_verbosity` in config file"));
+        assert!(format!("{error:#}").contains("takes `true` or `false`, or a number of times"));
+    }
+}
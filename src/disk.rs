@@ -0,0 +1,60 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::Result;
+use std::path::Path;
+
+/// Whether, and how strictly, to check for enough disk space before launching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum DiskCheck {
+    Off,
+    Warn,
+    Enforce,
+}
+
+/// Bytes available to unprivileged users on the filesystem `path` is, or will be created, on.
+///
+/// `None` when this isn't supported on the platform.
+pub(crate) fn available_space(path: &Path) -> Result<Option<u64>> {
+    // the dir may not have been created yet, so check the closest one which exists
+    let existing = path
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .unwrap_or_else(|| Path::new("."));
+
+    statvfs_available(existing)
+}
+
+#[cfg(unix)]
+fn statvfs_available(path: &Path) -> Result<Option<u64>> {
+    use eyre::eyre;
+    use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| eyre!("Invalid path {}", path.display()))?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `c_path` is a valid NUL-terminated string, and `stat` is only read once `statvfs`
+    // has initialised it, which it signals by returning 0.
+    if unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(eyre!(
+            "Could not query the free space of {}: {}",
+            path.display(),
+            std::io::Error::last_os_error()
+        ));
+    }
+    let stat = unsafe { stat.assume_init() };
+
+    #[allow(clippy::unnecessary_cast)] // the field types differ between platforms
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn statvfs_available(_path: &Path) -> Result<Option<u64>> {
+    Ok(None)
+}
@@ -0,0 +1,85 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+#[cfg(windows)]
+use crate::churn;
+#[cfg(unix)]
+use eyre::eyre;
+use eyre::Result;
+use std::{sync::atomic::AtomicBool, time::Duration};
+#[cfg(unix)]
+use std::{sync::atomic::Ordering, thread, time::Instant};
+
+/// How often the flag raised when a child process exits is checked while sleeping until one does.
+#[cfg(unix)]
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Set by the SIGCHLD handler when a child process exits.
+#[cfg(unix)]
+static CHILD_EXITED: AtomicBool = AtomicBool::new(false);
+
+/// Start noticing child processes exiting as soon as they do (with a SIGCHLD handler), for
+/// [`take_exited`], so long-running modes needn't wait for their next poll to notice.
+#[cfg(unix)]
+pub(crate) fn watch() -> Result<()> {
+    extern "C" fn on_sigchld(_signal: libc::c_int) {
+        // only an atomic store, which is async-signal-safe
+        CHILD_EXITED.store(true, Ordering::SeqCst);
+    }
+
+    // SAFETY: a zeroed `sigaction` is valid, and the handler is async-signal-safe. SA_RESTART
+    // keeps the handler from interrupting syscalls, and SA_NOCLDSTOP from firing on paused nodes.
+    let result = unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_sigchld as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART | libc::SA_NOCLDSTOP;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGCHLD, &action, std::ptr::null_mut())
+    };
+    if result != 0 {
+        return Err(eyre!(
+            "Could not install a SIGCHLD handler: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// Child process exits aren't signalled on Windows, so they're only noticed by polling.
+#[cfg(windows)]
+pub(crate) fn watch() -> Result<()> {
+    Ok(())
+}
+
+/// Whether a child process exited since this was last called, once [`watch`] was.
+#[cfg(unix)]
+pub(crate) fn take_exited() -> bool {
+    CHILD_EXITED.swap(false, Ordering::SeqCst)
+}
+
+#[cfg(windows)]
+pub(crate) fn take_exited() -> bool {
+    false
+}
+
+/// Sleep until a child process exits, once [`watch`] was called, `stop` is raised, or `timeout`
+/// has passed, whichever comes first.
+#[cfg(unix)]
+pub(crate) fn sleep_until_exited(timeout: Duration, stop: &AtomicBool) {
+    let started = Instant::now();
+    while !take_exited() && !stop.load(Ordering::SeqCst) && started.elapsed() < timeout {
+        thread::sleep(EXIT_CHECK_INTERVAL);
+    }
+}
+
+/// Exits aren't signalled on Windows, so this sleeps for all of `timeout` unless stopped.
+#[cfg(windows)]
+pub(crate) fn sleep_until_exited(timeout: Duration, stop: &AtomicBool) {
+    let _ = churn::sleep_unless_stopped(timeout, stop);
+}
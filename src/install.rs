@@ -0,0 +1,87 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{Result, WrapErr};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, warn};
+
+/// How the node binary is placed into each node's dir.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum InstallMode {
+    Copy,
+    Hardlink,
+    Symlink,
+}
+
+/// Place `binary` into `node_dir` according to `mode`, at its [`installed_path`].
+///
+/// Links which can't be created (e.g. a hardlink across filesystems, or a symlink on Windows
+/// without the required privilege) fall back to a copy.
+pub(crate) fn install_binary(binary: &Path, node_dir: &Path, mode: InstallMode) -> Result<()> {
+    let target = installed_path(binary, node_dir);
+
+    fs::create_dir_all(node_dir)
+        .wrap_err_with(|| format!("Could not create directory {}", node_dir.display()))?;
+    // a binary left over by a previous run may be stale, or may be a link to another one
+    match fs::remove_file(&target) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => {
+            return Err(error)
+                .wrap_err_with(|| format!("Could not remove old binary {}", target.display()));
+        }
+        _ => {}
+    }
+
+    let linked = match mode {
+        InstallMode::Copy => None,
+        InstallMode::Hardlink => Some(fs::hard_link(binary, &target)),
+        InstallMode::Symlink => Some(symlink(binary, &target)),
+    };
+    match linked {
+        Some(Ok(())) => {
+            debug!("Linked {} to {}", target.display(), binary.display());
+            return Ok(());
+        }
+        Some(Err(error)) => warn!(
+            "Could not {:?} {} into {}, copying it instead: {}",
+            mode,
+            binary.display(),
+            node_dir.display(),
+            error
+        ),
+        None => {}
+    }
+
+    fs::copy(binary, &target).wrap_err_with(|| {
+        format!(
+            "Could not copy {} to {}",
+            binary.display(),
+            target.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Where `binary` is placed in `node_dir` by [`install_binary`].
+pub(crate) fn installed_path(binary: &Path, node_dir: &Path) -> PathBuf {
+    node_dir.join(binary.file_name().unwrap_or(binary.as_os_str()))
+}
+
+#[cfg(unix)]
+fn symlink(binary: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::canonicalize(binary)?, target)
+}
+
+#[cfg(windows)]
+fn symlink(binary: &Path, target: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(fs::canonicalize(binary)?, target)
+}
@@ -0,0 +1,123 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::process;
+use eyre::{eyre, Result, WrapErr};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, MutexGuard, OnceLock, Weak,
+};
+use tracing::{debug, warn};
+
+/// Exit code of a process interrupted by SIGINT, as shells report it.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// What the Ctrl-C handler acts on, for as long as it's in use.
+static INTERRUPTIBLE: Mutex<Interruptible> = Mutex::new(Interruptible {
+    spawned: Vec::new(),
+    stops: Vec::new(),
+});
+
+/// Outcome of installing the Ctrl-C handler, which can only be done once per process.
+static HANDLER: OnceLock<Result<(), String>> = OnceLock::new();
+
+/// PIDs of the nodes spawned so far, which are killed when the user presses Ctrl-C.
+#[derive(Clone, Default)]
+pub(crate) struct Spawned(Arc<Mutex<Vec<u32>>>);
+
+impl Spawned {
+    /// Kill all the nodes later recorded with [`Spawned::push`] when the user presses Ctrl-C,
+    /// before exiting.
+    pub(crate) fn kill_on_ctrl_c() -> Result<Self> {
+        install_handler()?;
+        let spawned = Self::default();
+        let mut interruptible = lock(&INTERRUPTIBLE);
+        interruptible
+            .spawned
+            .retain(|spawned| spawned.strong_count() > 0);
+        interruptible.spawned.push(Arc::downgrade(&spawned.0));
+        Ok(spawned)
+    }
+
+    pub(crate) fn push(&self, pid: u32) {
+        lock(&self.0).push(pid)
+    }
+}
+
+/// A flag raised when the user presses Ctrl-C, so a long-running loop can stop cleanly instead of
+/// the process being killed halfway through launching a node. Nodes to be killed on Ctrl-C take
+/// precedence, the process exiting once they are.
+pub(crate) fn stop_on_ctrl_c() -> Result<Arc<AtomicBool>> {
+    install_handler()?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut interruptible = lock(&INTERRUPTIBLE);
+    interruptible.stops.retain(|stop| stop.strong_count() > 0);
+    interruptible.stops.push(Arc::downgrade(&stop));
+    Ok(stop)
+}
+
+/// The PIDs to kill and the flags to raise on Ctrl-C, dropped by whoever registered them once
+/// they're done with them.
+struct Interruptible {
+    spawned: Vec<Weak<Mutex<Vec<u32>>>>,
+    stops: Vec<Weak<AtomicBool>>,
+}
+
+fn install_handler() -> Result<()> {
+    HANDLER
+        .get_or_init(|| ctrlc::set_handler(on_ctrl_c).map_err(|error| error.to_string()))
+        .clone()
+        .map_err(|error| eyre!(error))
+        .wrap_err("Could not install a Ctrl-C handler")
+}
+
+/// Kill the nodes recorded to be killed and exit, or else raise the stop flags in use, or else
+/// exit as if there were no handler.
+fn on_ctrl_c() {
+    let interruptible = lock(&INTERRUPTIBLE);
+    let spawned: Vec<_> = interruptible
+        .spawned
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect();
+    let stops: Vec<_> = interruptible
+        .stops
+        .iter()
+        .filter_map(Weak::upgrade)
+        .collect();
+    if spawned.is_empty() && !stops.is_empty() {
+        for stop in stops {
+            stop.store(true, Ordering::SeqCst);
+        }
+        return;
+    }
+
+    let pids: Vec<u32> = spawned
+        .iter()
+        .rev()
+        .flat_map(|pids| lock(pids).iter().rev().copied().collect::<Vec<_>>())
+        .collect();
+    if !pids.is_empty() {
+        warn!("Interrupted, killing the {} nodes launched", pids.len());
+    }
+    // in reverse order, so genesis, always spawned first, is killed last
+    for pid in pids {
+        // the node may well have exited already
+        if let Err(error) = process::kill(pid) {
+            debug!("Could not kill PID {}: {:?}", pid, error);
+        }
+    }
+    std::process::exit(INTERRUPTED_EXIT_CODE);
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
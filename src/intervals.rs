@@ -0,0 +1,85 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info};
+
+/// The waits between launching nodes: the base interval, plus a random offset of up to the jitter
+/// when there's one, drawn from a seeded generator so a launch's waits can be reproduced.
+pub(crate) struct Intervals {
+    base_ms: u64,
+    jitter_ms: u64,
+    rng: SplitMix64,
+    chosen: Vec<Duration>,
+}
+
+impl Intervals {
+    pub(crate) fn new(base_ms: u64, jitter_ms: Option<u64>, seed: u64) -> Self {
+        let jitter_ms = jitter_ms.unwrap_or(0);
+        if jitter_ms > 0 {
+            info!(
+                "Jittering the launch intervals by up to {}ms with seed {} (pass --seed {} to reproduce them)",
+                jitter_ms, seed, seed
+            );
+        }
+
+        Self {
+            base_ms,
+            jitter_ms,
+            rng: SplitMix64(seed),
+            chosen: vec![],
+        }
+    }
+
+    /// Choose how long to wait after launching node `idx`.
+    pub(crate) fn next(&mut self, idx: usize) -> Duration {
+        let offset_ms = match self.jitter_ms {
+            0 => 0,
+            jitter_ms => self.rng.next() % (jitter_ms + 1),
+        };
+        let interval = Duration::from_millis(self.base_ms + offset_ms);
+        if self.jitter_ms > 0 {
+            debug!(
+                "Waiting {}ms ({}ms + {}ms jitter) after node #{}",
+                interval.as_millis(),
+                self.base_ms,
+                offset_ms,
+                idx
+            );
+        }
+        self.chosen.push(interval);
+
+        interval
+    }
+
+    /// The intervals chosen so far, in order.
+    pub(crate) fn chosen(&self) -> &[Duration] {
+        &self.chosen
+    }
+}
+
+/// A seed for the launch's random choices when none was given, from the current time.
+pub(crate) fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_nanos() as u64)
+}
+
+/// A small, fast PRNG, good enough for spreading launches out and stable across platforms.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
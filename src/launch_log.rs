@@ -0,0 +1,98 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::{
+    filter::Targets,
+    fmt::{self, MakeWriter},
+    registry::LookupSpan,
+    Layer,
+};
+
+/// File in the nodes dir the launcher's own messages are written to.
+pub(crate) const LAUNCH_LOG_FILE: &str = "launch.log";
+
+/// The launch log of the current launch, if any: its path, and the file once the first message
+/// has been written to it.
+static LAUNCH_LOG: Mutex<Option<(PathBuf, Option<File>)>> = Mutex::new(None);
+
+/// Have the messages of [`layer`] appended to the launch log in `nodes_dir` from now on.
+pub(crate) fn start(nodes_dir: &Path) {
+    *lock() = Some((nodes_dir.join(LAUNCH_LOG_FILE), None));
+}
+
+/// Tracing layer writing the tool's messages, from debug level on regardless of RUST_LOG, to the
+/// launch log of the current launch, with their timestamps. Messages are dropped until a launch
+/// starts.
+pub(crate) fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fmt::layer()
+        .with_ansi(false)
+        .with_writer(LaunchLogWriter)
+        .with_filter(Targets::new().with_target(env!("CARGO_CRATE_NAME"), Level::DEBUG))
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<(PathBuf, Option<File>)>> {
+    LAUNCH_LOG
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+struct LaunchLogWriter;
+
+impl<'a> MakeWriter<'a> for LaunchLogWriter {
+    type Writer = LaunchLogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LaunchLogWriter
+    }
+}
+
+impl Write for LaunchLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut launch_log = lock();
+        let (path, file) = match launch_log.as_mut() {
+            Some(launch_log) => launch_log,
+            None => return Ok(buf.len()),
+        };
+        if file.is_none() {
+            let opened = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|()| OpenOptions::new().create(true).append(true).open(&*path));
+            match opened {
+                Ok(opened) => *file = Some(opened),
+                // nothing can be logged about it from here, so the launch goes on without its log
+                Err(_) => {
+                    *launch_log = None;
+                    return Ok(buf.len());
+                }
+            }
+        }
+        match file {
+            Some(file) => file.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match lock().as_mut() {
+            Some((_, Some(file))) => file.flush(),
+            _ => Ok(()),
+        }
+    }
+}
@@ -7,22 +7,74 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
+mod addr_map;
+mod address;
+mod capacity;
+mod churn;
 mod cmd;
+mod compose;
+mod config;
+mod disk;
+mod exits;
+mod install;
+mod interrupt;
+mod intervals;
+mod launch_log;
+mod list;
+mod logs;
+mod memory;
+mod naming;
+mod netem;
+mod nofile;
+mod order;
+mod output;
+mod probe;
+mod process;
+mod profiles;
+mod readiness;
+mod resilience;
+mod restart;
+mod sanitizer;
+mod script;
+mod snapshot;
+mod statsd;
+mod systemd;
+mod tags;
+mod tail;
+mod timings;
+#[cfg(feature = "tui")]
+mod tui;
+mod verify;
 
 use eyre::{eyre, Result, WrapErr};
 use std::{
     borrow::Cow,
+    collections::BTreeMap,
     env,
+    ffi::{OsStr, OsString},
     fs::{self},
-    net::SocketAddr,
-    ops::RangeInclusive,
-    path::PathBuf,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
+    process::Child,
+    sync::{atomic::Ordering, mpsc::Receiver},
     thread,
-    time::Duration,
+    time::{Duration, Instant, SystemTime},
 };
-use tracing::{debug, info};
+use tracing::{debug, info, info_span, warn, Span};
 
+use addr_map::NodeAddrs;
+use capacity::{CapacityDist, CapacityList};
 use cmd::NodeCmd;
+use disk::DiskCheck;
+use install::InstallMode;
+use naming::{NamingArgs, GENESIS_IDX};
+use order::{LaunchOrder, StopOrder};
+use output::ExportShell;
+use process::Restarts;
+use readiness::{Readiness, ReadinessStrategy};
+use sanitizer::Sanitizer;
+use tags::with_node_tag;
 
 #[cfg(not(target_os = "windows"))]
 const SN_NODE_EXECUTABLE: &str = "sn_node";
@@ -32,19 +84,125 @@ const SN_NODE_EXECUTABLE: &str = "sn_node.exe";
 
 const DEFAULT_RUST_LOG: &str = "safe_network=debug";
 
+/// Name of the network contacts file clients bootstrap with by default, in
+/// [`client_network_contacts_dir`].
+const DEFAULT_NETWORK_CONTACTS: &str = "default";
+
+/// Suffix of the --contacts-addrs file written next to each copy of the network contacts, e.g.
+/// `default.addresses.json`.
+const CONTACTS_ADDRS_SUFFIX: &str = ".addresses.json";
+
+/// Network contacts file genesis writes in its node dir, which the other nodes join with.
+const GENESIS_CONTACTS_FILE: &str = "section_tree";
+
+/// How long to wait for a node to log the address it's listening on.
+const NODE_ADDRESS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest network launched without --force, as each node is a process of its own.
+const MAX_NODES_WITHOUT_FORCE: usize = 1000;
+
+/// How often genesis is checked for having written its network contacts.
+const CONTACTS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How often the nodes are checked for having exited with --wait, besides as soon as one does where
+/// that's signalled (Unix).
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long the nodes of a previous run are given to exit with --kill-existing before being
+/// killed outright.
+const KILL_EXISTING_GRACE: Duration = Duration::from_secs(10);
+
+type AddressHook<'a, 'f> = &'a mut (dyn FnMut(usize, &str, SocketAddr) + 'f);
+
 /// Tool to launch Safe nodes to form a local single-section network
 ///
 /// Currently, this tool runs nodes on localhost (since that's the default if no IP address is given to the nodes)
-#[derive(Debug, clap::StructOpt)]
+#[derive(Clone, Debug, clap::StructOpt)]
 #[clap(version)]
+#[clap(group = clap::ArgGroup::new("env-output").args(&["export-env", "summary-only"]))]
+#[clap(group = clap::ArgGroup::new("randomness").args(&["interval-jitter", "shuffle-order", "capacity-dist"]).multiple(true))]
 pub struct Launch {
     #[clap(flatten)]
     common: CommonArgs,
 
+    #[clap(flatten)]
+    naming: NamingArgs,
+
+    /// Preset bundle of flags for a common scenario, which flags given explicitly override (see
+    /// --list-profiles). Only expanded by the `sn_launch_tool` binary, or by parsing the args
+    /// returned by `Launch::expand_args`.
+    #[clap(long, value_enum)]
+    profile: Option<profiles::Profile>,
+
+    /// TOML file of launch options, e.g. `num_nodes = 20`, named as their long flags in snake or
+    /// kebab case. A flag without a value is set with `true`, and a list gives the flag once per
+    /// value. Flags given explicitly override the file's, which override --profile's. Only read
+    /// by the `sn_launch_tool` binary, or by parsing the args returned by `Launch::expand_args`.
+    #[clap(long, value_parser)]
+    config: Option<PathBuf>,
+
+    /// List the --profile presets along with their flags, and exit.
+    #[clap(long, value_parser)]
+    list_profiles: bool,
+
     /// Interval in milliseconds between launching each of the nodes
     #[clap(short = 'i', long, default_value = "100", value_parser)]
     interval: u64,
 
+    /// Add a random offset of up to this many milliseconds to each interval between launching
+    /// nodes. The offsets are logged at debug level, and can be reproduced with --seed.
+    #[clap(long, value_parser)]
+    interval_jitter: Option<u64>,
+
+    /// Seed for the random offsets of --interval-jitter, the order of --shuffle-order and the
+    /// capacities of --capacity-dist, to reproduce those of a previous launch. By default a new seed is picked and logged.
+    #[clap(long, requires = "randomness", value_parser)]
+    seed: Option<u64>,
+
+    /// Check that there's enough free disk space for the nodes on the filesystem of --nodes-dir
+    /// before launching them, estimating each node needs --est-node-disk-mb. With `warn` a lack of
+    /// space is only reported, with `enforce` it fails the launch.
+    #[clap(long, value_enum, default_value = "off")]
+    disk_check: DiskCheck,
+
+    /// Estimated disk space in MiB each node needs for its data and logs, for --disk-check.
+    #[clap(long, default_value = "100", value_parser)]
+    est_node_disk_mb: u64,
+
+    /// Before launching each node after genesis, check the system has at least this many MiB of
+    /// memory available, and otherwise pause until it does, so the last nodes of a large network
+    /// don't push the machine into swap. The launch is aborted if memory doesn't recover within
+    /// --min-free-mem-timeout.
+    #[clap(long, value_parser)]
+    min_free_mem: Option<u64>,
+
+    /// Seconds to wait for memory to recover when paused by --min-free-mem before aborting.
+    #[clap(long, default_value = "60", requires = "min-free-mem", value_parser)]
+    min_free_mem_timeout: u64,
+
+    /// Storage limit of each node being launched, in index order (genesis first), as a comma
+    /// separated list of sizes with an optional unit, e.g. `1GB,1GB,500MB`. It must list one size
+    /// per node. Each is forwarded to its node with --capacity-flag.
+    #[clap(
+        long,
+        conflicts_with_all = &["churn-add", "emit-compose"],
+        value_parser = CapacityList::parse
+    )]
+    capacities: Option<CapacityList>,
+
+    /// Distribution to sample each node's storage limit from, e.g. `uniform:500MB-2GB`, instead of
+    /// listing them with --capacities. The samples are reproducible with --seed.
+    #[clap(
+        long,
+        conflicts_with_all = &["capacities", "churn-add", "emit-compose"],
+        value_parser = CapacityDist::parse
+    )]
+    capacity_dist: Option<CapacityDist>,
+
+    /// sn_node flag the storage limits of --capacities or --capacity-dist are passed with, in bytes.
+    #[clap(long, default_value = "--max-capacity", value_parser)]
+    capacity_flag: String,
+
     /// Interval in seconds before deeming a peer to have timed out
     #[clap(long = "idle-timeout-msec", value_parser)]
     idle_timeout_msec: Option<u64>,
@@ -53,152 +211,2672 @@ pub struct Launch {
     #[clap(long = "keep-alive-interval-msec", value_parser)]
     keep_alive_interval_msec: Option<u64>,
 
+    /// Maximum number of concurrent connections each node accepts, to test the network under a
+    /// constrained connection budget. Ignored, with a warning, if the sn_node binary doesn't
+    /// support --max-connections-flag.
+    #[clap(long, value_parser)]
+    max_connections: Option<u32>,
+
+    /// sn_node flag the limit of --max-connections is passed with.
+    #[clap(
+        long,
+        default_value = "--max-connections",
+        requires = "max-connections",
+        value_parser
+    )]
+    max_connections_flag: String,
+
     /// Path where the output directories for all the nodes are written
     #[clap(short = 'd', long, default_value = "./nodes", value_parser)]
     nodes_dir: PathBuf,
 
-    /// Number of nodes to spawn with the first one being the genesis. This number should be greater than 0.
+    /// Number of nodes to spawn with the first one being the genesis. This number must be greater
+    /// than 0, and networks of more than 1000 nodes need --force.
+    /// With `-`, the number is read from the first line of stdin instead, e.g. for
+    /// `echo 20 | sn_launch_tool --num-nodes -`, so the tool waits for it when stdin is a terminal.
     #[clap(
         short = 'n',
         long,
         default_value = "15",
         env = "NODE_COUNT",
-        value_parser
+        value_parser = parse_num_nodes
     )]
     num_nodes: usize,
 
-    /// IP used to launch the nodes with.
-    #[clap(long = "ip", value_parser)]
-    ip: Option<String>,
+    /// Launch networks of more than 1000 nodes, which are refused otherwise as each node is a
+    /// process of its own.
+    #[clap(long, value_parser)]
+    force: bool,
+
+    /// IP used to launch the nodes with. Repeat it, or separate IPs with commas, to spread the
+    /// nodes across several IPs, e.g. loopback aliases such as 127.0.0.2, which must be configured
+    /// on the host. The IPs are assigned round-robin in node index order, genesis getting the
+    /// first.
+    #[clap(long = "ip", value_delimiter = ',', value_parser)]
+    ip: Vec<String>,
+
+    /// Port genesis binds to, each node #N binding to the port N - 1 after it, rather than to an
+    /// ephemeral port. Requires --ip or --local.
+    #[clap(long, value_parser = clap::value_parser!(u16).range(1..))]
+    base_port: Option<u16>,
 
     /// IP used to launch the nodes with.
     #[clap(long = "add", value_parser)]
     add_nodes_to_existing_network: bool,
+
+    /// Before launching, stop the nodes of a previous run listed in the nodes dir's `network.pids`,
+    /// killing those still running after a few seconds, and remove the nodes dir, so the network
+    /// starts afresh. The nodes dir can't be the root, home or current dir, nor hold them.
+    #[clap(
+        long,
+        conflicts_with_all = &["add-nodes-to-existing-network", "dry-run"],
+        value_parser
+    )]
+    kill_existing: bool,
+
+    /// Grow an existing network by a percentage of its current size, e.g. `20%`, instead of by
+    /// a fixed number of nodes. The number of nodes to add is rounded up. Requires --add.
+    #[clap(
+        long,
+        requires = "add-nodes-to-existing-network",
+        conflicts_with = "num-nodes",
+        value_parser = parse_percentage
+    )]
+    grow: Option<u32>,
+
+    /// Keep adding nodes to an existing network at this rate, in nodes per minute, until
+    /// interrupted with Ctrl-C or --churn-add-cap nodes have been added, instead of adding a fixed
+    /// number of them. Requires --add. Ctrl-C in the terminal also reaches the nodes, unless they
+    /// were started with --detach.
+    #[clap(
+        long,
+        requires = "add-nodes-to-existing-network",
+        conflicts_with_all = &["grow", "behind-nat", "dry-run"],
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    churn_add: Option<u32>,
+
+    /// Maximum number of nodes to add with --churn-add.
+    #[clap(long, requires = "churn-add", value_parser)]
+    churn_add_cap: Option<usize>,
+
+    /// Order to launch the nodes after genesis in, instead of by ascending index, as a comma
+    /// separated list of node indices and ranges, e.g. `8-15,2-7` to launch nodes 8 to 15 before
+    /// nodes 2 to 7, or `15-2` for descending order. It must list every node being launched
+    /// exactly once.
+    #[clap(long, conflicts_with = "churn-add", value_parser = LaunchOrder::parse)]
+    launch_order: Option<LaunchOrder>,
+
+    /// Launch the nodes after genesis in a random order, to surface any dependence on the launch
+    /// order. The nodes keep their indices and dir names. The order is logged, and can be
+    /// reproduced with --seed.
+    #[clap(long, conflicts_with_all = &["churn-add", "launch-order"], value_parser)]
+    shuffle_order: bool,
+
+    /// Launch the nodes after genesis in batches of up to this many at once, rather than one at a
+    /// time, each still going through the liveness check. The --interval is then waited once per
+    /// batch, while other --readiness strategies still wait for each node. Genesis is always
+    /// launched, and ready, first.
+    #[clap(
+        long,
+        conflicts_with = "churn-add",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    parallel: Option<u64>,
+
+    /// Transport protocol the nodes should use, if the sn_node binary supports selecting one.
+    /// The QUIC-only --idle-timeout-msec and --keep-alive-interval-msec are ignored with `tcp`.
+    #[clap(long, value_enum)]
+    transport: Option<Transport>,
+
+    /// Inject this many milliseconds of latency on the loopback interface once the nodes are
+    /// launched, to simulate a slow network (Linux only, requires `tc` and root privileges).
+    /// The delay is left in place after the tool exits, until the network is stopped with --wait
+    /// or `stop`, or removed with `tc qdisc del dev lo root netem`.
+    #[clap(long, value_parser)]
+    inject_latency: Option<u64>,
+
+    /// Fail the launch if the network contacts can't be copied to the default location for
+    /// clients. Otherwise it's reported as a warning, since the network is up regardless.
+    #[clap(long, value_parser)]
+    require_contacts_copy: bool,
+
+    /// Seconds to wait once the nodes are launched before copying the network contacts for
+    /// clients, e.g. for the section to settle after the last nodes joined.
+    #[clap(long, value_parser)]
+    contacts_copy_delay: Option<u64>,
+
+    /// Only copy the network contacts for clients once genesis' section_tree hasn't changed, in
+    /// size or modification time, for this many seconds, so clients bootstrap from a settled
+    /// section rather than one captured mid-formation.
+    #[clap(long, value_parser)]
+    contacts_quiet_secs: Option<u64>,
+
+    /// Seconds to wait at most for the --contacts-quiet-secs period, after which the network
+    /// contacts are copied anyway, with a warning.
+    #[clap(
+        long,
+        default_value = "300",
+        requires = "contacts-quiet-secs",
+        value_parser
+    )]
+    contacts_quiet_timeout: u64,
+
+    /// Alongside each copy of the network contacts, also write the nodes' addresses as JSON to a
+    /// file of the same name followed by `.addresses.json`, e.g. `default.addresses.json`, in the
+    /// same format as --addrs-json, for clients which want a readable list of contacts.
+    #[clap(long, value_parser)]
+    contacts_addrs: bool,
+
+    /// Also copy the network contacts to this path, besides the default location for clients
+    /// (`~/.safe/network_contacts/default`). Can be given more than once. A copy which fails is
+    /// warned about without stopping the others.
+    #[clap(long, value_parser)]
+    contacts_output: Vec<PathBuf>,
+
+    /// Don't copy the network contacts to the default location for clients, only to the
+    /// --contacts-output paths.
+    #[clap(long, value_parser)]
+    no_default_contacts: bool,
+
+    /// Copy the network contacts for clients to `default` in this dir rather than in
+    /// `~/.safe/network_contacts`, leaving the contacts clients use by default untouched.
+    #[clap(long, conflicts_with = "no-default-contacts", value_parser)]
+    client_contacts_dir: Option<PathBuf>,
+
+    /// Overwrite the network contacts already copied for clients, rather than first moving them
+    /// aside to `default.bak.<unix time>` when they differ.
+    #[clap(long, conflicts_with_all = &["no-client-contacts", "no-default-contacts"], value_parser)]
+    no_backup: bool,
+
+    /// Don't copy the network contacts anywhere for clients, only logging where genesis' contacts
+    /// are for clients to be pointed at.
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "client-contacts-dir",
+            "contacts-addrs",
+            "contacts-copy-delay",
+            "contacts-output",
+            "contacts-quiet-secs",
+            "no-default-contacts",
+            "require-contacts-copy",
+        ],
+        value_parser
+    )]
+    no_client_contacts: bool,
+
+    /// Tag each node with an id made of this launch's id and the node's index, which stays the
+    /// same when nodes are added to the network or restarted, so their logs and metrics can be
+    /// told apart. The tag is passed in the `SN_NODE_TAG` env var, and as `--node-tag` if the
+    /// sn_node binary supports it. The launch id is recorded in the nodes dir.
+    #[clap(long, value_parser)]
+    tag_nodes: bool,
+
+    /// StatsD endpoint (`host:port`) to send the launch's metrics to over UDP once it's done or
+    /// has failed: the duration of the launch and each of its phases, the launch duration of each
+    /// joining node, the number of nodes respawned by --spawn-retries, and the number of nodes up
+    /// or of failed launches.
+    #[clap(long, conflicts_with = "emit-compose", value_parser)]
+    statsd: Option<SocketAddr>,
+
+    /// Prefix of the names of the metrics sent to --statsd.
+    #[clap(
+        long,
+        default_value = "sn_launch_tool",
+        requires = "statsd",
+        value_parser
+    )]
+    statsd_prefix: String,
+
+    /// Fail the launch on any of the conditions otherwise only reported as warnings: a
+    /// transport-specific flag being ignored, sn_node versions which may be incompatible, the
+    /// free disk space being too low (with `--disk-check warn`) or not checkable, the open files
+    /// limit being too low for the network, a node's address not being discovered, a connect
+    /// probe failing, the genesis address not being found for --export-env, and the network
+    /// contacts not being copied for clients.
+    #[clap(long, alias = "fail-on-warn", value_parser)]
+    strict: bool,
+
+    /// Shell command run repeatedly after launching genesis, until it exits successfully, before
+    /// launching the rest of the nodes. It's run with SN_GENESIS_CONTACTS, SN_NODES_DIR and, if
+    /// known, SN_GENESIS_ADDR set in its environment.
+    #[clap(long, value_parser)]
+    genesis_ready_cmd: Option<String>,
+
+    /// Seconds to wait for --genesis-ready-cmd to succeed before failing the launch.
+    #[clap(long, default_value = "60", value_parser)]
+    genesis_ready_timeout: u64,
+
+    /// Seconds to wait after genesis' readiness wait for it to have written its network contacts
+    /// (section_tree), which the other nodes join with, before failing the launch.
+    #[clap(long, default_value = "30", value_parser)]
+    genesis_contacts_timeout: u64,
+
+    /// How to tell each node is ready before launching the next one: wait for --interval
+    /// (`sleep`), for --readiness-file to appear in its dir (`file`), for --readiness-log-marker
+    /// to appear in its logs (`log-marker`), for its address to be reachable (`connect`), or for
+    /// --readiness-cmd to succeed (`command`). --genesis-ready-cmd takes precedence for genesis.
+    #[clap(long, value_enum, default_value = "sleep")]
+    readiness: Readiness,
+
+    /// File, relative to each node's dir, whose appearance means the node is ready, with
+    /// `--readiness file`.
+    #[clap(long, required_if_eq("readiness", "file"), value_parser)]
+    readiness_file: Option<PathBuf>,
+
+    /// Text whose appearance in a node's logs means it's ready, with `--readiness log-marker`.
+    #[clap(long, default_value = "connection info", value_parser)]
+    readiness_log_marker: String,
+
+    /// Shell command run repeatedly after launching each node until it exits successfully, with
+    /// `--readiness command`. It's run with SN_NODE_NAME, SN_NODE_DIR, SN_NODES_DIR and, if
+    /// known, SN_NODE_ADDR set in its environment.
+    #[clap(long, required_if_eq("readiness", "command"), value_parser)]
+    readiness_cmd: Option<String>,
+
+    /// Seconds to wait for each node to be ready with --readiness strategies other than `sleep`
+    /// before failing the launch, and for each node restarted by --rolling-restart to be
+    /// reachable again.
+    #[clap(long, default_value = "60", value_parser)]
+    readiness_timeout: u64,
+
+    /// Learn genesis' address from the connection info line it prints on its stdout, which is piped
+    /// through the tool, rather than from its log files, which are only read if the line isn't
+    /// seen within a few seconds.
+    #[clap(long, conflicts_with_all = &["detach", "capture-output"], value_parser)]
+    genesis_addr_from_stdout: bool,
+
+    /// Directory with a data snapshot to seed the genesis node's root dir with before launching it,
+    /// so the network starts in a known state. If the snapshot contains an `sn_node_version` file,
+    /// it must match the `sn_node -V` output of the binary being launched.
+    #[clap(long, conflicts_with = "add-nodes-to-existing-network", value_parser)]
+    genesis_snapshot: Option<PathBuf>,
+
+    /// Relaunch genesis from the data of an existing node dir, e.g. one of a previously stopped
+    /// network, rather than creating a fresh genesis with `--first`, so its identity and state
+    /// persist. The dir must hold the network contacts (`section_tree`) genesis writes. Unless it's
+    /// the genesis dir itself, it's copied into the genesis dir, which mustn't exist yet.
+    #[clap(
+        long,
+        conflicts_with_all = &["add-nodes-to-existing-network", "genesis-snapshot"],
+        value_parser
+    )]
+    genesis_from: Option<PathBuf>,
+
+    /// Number of joining nodes (the highest-numbered ones, whatever order they're launched in) to
+    /// simulate being behind NAT. Those nodes are only given a --local-addr, while the rest are
+    /// also given a reachable --public-addr on the same IP. Requires --ip or --local.
+    #[clap(long, value_parser)]
+    behind_nat: Option<usize>,
+
+    /// File mapping node indices (genesis being #1) to the local address each node binds to and,
+    /// optionally, the public address it advertises, one `<index> <local addr> [<public addr>]`
+    /// per line. Nodes which aren't mapped use the address given by --ip or --local, if any.
+    #[clap(long, conflicts_with = "behind-nat", value_parser)]
+    addr_map: Option<PathBuf>,
+
+    /// When a fixed port in --addr-map is already in use, try up to this many of the following
+    /// ports instead of failing the launch. Ports are checked before any node is launched.
+    #[clap(long, requires = "addr-map", value_parser)]
+    port_search: Option<u16>,
+
+    /// Once launched, print a ready-to-run `sn_launch_tool join` command to add another node to
+    /// this network.
+    #[clap(long, value_parser)]
+    print_join_cmd: bool,
+
+    /// Once launched, print shell statements exporting SN_GENESIS_ADDR, SN_GENESIS_CONTACTS and
+    /// SN_NODES_DIR, e.g. for `eval "$(sn_launch_tool --export-env ...)"`. Only those statements are
+    /// written to stdout: logs, including the nodes' output, go to stderr.
+    #[clap(long, conflicts_with = "print-join-cmd", value_parser)]
+    export_env: bool,
+
+    /// Shell syntax of the --export-env statements (default: powershell on Windows, sh elsewhere).
+    #[clap(long, value_enum, requires = "env-output")]
+    export_shell: Option<ExportShell>,
+
+    /// Only print the --export-env statements once launched, for scripts: the tool's logs are
+    /// suppressed except for errors, which go to stderr, and each node's stdout and stderr are
+    /// written to `stdout.log` and `stderr.log` in its dir.
+    #[clap(long, conflicts_with = "print-join-cmd", value_parser)]
+    summary_only: bool,
+
+    /// Write a hosts-file style mapping of each node's IP to its name (e.g. `127.0.0.1 sn-node-3`)
+    /// to this path. This is a standalone file, not `/etc/hosts`, to be included or merged as desired.
+    #[clap(long, value_parser)]
+    hosts_file: Option<PathBuf>,
+
+    /// Write a JSON array of the nodes' addresses, each with the node's index and name, to this
+    /// path, or to stdout with `-`, for clients to use as bootstrap contacts besides genesis.
+    #[clap(long, conflicts_with_all = &["export-env", "print-join-cmd"], value_parser)]
+    addrs_json: Option<PathBuf>,
+
+    /// Write a JSON summary of the launched network to this path once the launch has succeeded:
+    /// the number of nodes, each node's name, root and log dirs, address, --tag-nodes tag, how
+    /// many times it was restarted and how it last exited, and the files the network contacts were
+    /// copied to for clients.
+    #[clap(long, value_parser)]
+    summary_json: Option<PathBuf>,
+
+    /// Once launched, check that no two nodes were discovered listening on the same address, and
+    /// fail listing the duplicates otherwise. When adding nodes, the existing nodes' addresses are
+    /// read back from their logs to be checked too.
+    #[clap(long, value_parser)]
+    check_distinct_addrs: bool,
+
+    /// After launching each node, wait for its address to be reachable: accepting TCP connections
+    /// with `--transport tcp`, or else having its UDP socket bound. Nodes which don't become
+    /// reachable are reported, without failing the launch.
+    #[clap(long, value_parser)]
+    connect_probe: bool,
+
+    /// Seconds to keep retrying the --connect-probe of each node for.
+    #[clap(long, default_value = "10", requires = "connect-probe", value_parser)]
+    connect_probe_timeout: u64,
+
+    /// Milliseconds between --connect-probe attempts.
+    #[clap(long, default_value = "200", requires = "connect-probe", value_parser)]
+    connect_probe_interval_msec: u64,
+
+    /// Once launched, restart the nodes one at a time reusing their data dirs, to exercise the
+    /// network's tolerance to sequential restarts (e.g. an upgrade). Genesis is restarted last.
+    /// Each node is waited for to be reachable again on the address it logs once restarted,
+    /// before moving on.
+    #[clap(long, value_parser)]
+    rolling_restart: bool,
+
+    /// Seconds to wait after restarting a node before restarting the next one.
+    #[clap(long, default_value = "5", requires = "rolling-restart", value_parser)]
+    restart_settle_secs: u64,
+
+    /// Restart the nodes using this sn_node binary instead, e.g. to test an upgrade.
+    #[clap(long, requires = "rolling-restart", value_parser)]
+    restart_node_path: Option<PathBuf>,
+
+    /// Restart genesis first rather than last.
+    #[clap(long, requires = "rolling-restart", value_parser)]
+    restart_genesis_first: bool,
+
+    /// Once launched (and restarted with --rolling-restart), wait this many seconds for the network
+    /// to settle, then run the --verify checks, logging whether each passed, and fail the launch
+    /// (exiting with an error status) if any didn't.
+    #[clap(long, value_parser)]
+    verify_after: Option<u64>,
+
+    /// Comma separated checks run by --verify-after: the launched nodes are all still running
+    /// (`alive`), were discovered on distinct addresses (`distinct-addrs`) and are reachable
+    /// (`reachable`), and --verify-cmd succeeds (`smoke`).
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "alive,distinct-addrs",
+        requires = "verify-after"
+    )]
+    verify: Vec<verify::Check>,
+
+    /// Shell command run once by the `smoke` check of --verify, e.g. a client test against the
+    /// network. It's run with SN_GENESIS_CONTACTS and SN_NODES_DIR set in its environment.
+    #[clap(long, requires = "verify-after", value_parser)]
+    verify_cmd: Option<String>,
+
+    /// Once launched, test the network survives losing genesis: kill genesis, wait
+    /// --resilience-settle-secs, then check the rest of the nodes are still running and reachable,
+    /// failing the launch otherwise.
+    #[clap(
+        long,
+        conflicts_with_all = &["add-nodes-to-existing-network", "rolling-restart"],
+        value_parser
+    )]
+    test_genesis_resilience: bool,
+
+    /// Milliseconds to wait for nodes killed by the tool to exit, e.g. genesis with
+    /// --test-genesis-resilience, before reporting those which haven't.
+    #[clap(long, default_value = "10000", value_parser)]
+    shutdown_timeout_msec: u64,
+
+    /// Seconds to wait after killing genesis before checking the rest of the nodes.
+    #[clap(
+        long,
+        default_value = "30",
+        requires = "test-genesis-resilience",
+        value_parser
+    )]
+    resilience_settle_secs: u64,
+
+    /// Place the sn_node binary into each node's dir by copying, hardlinking or symlinking it, and
+    /// run each node from there. Links fall back to a copy when they can't be created, e.g. across
+    /// filesystems, or for symlinks on Windows without Developer Mode or admin rights.
+    #[clap(long, value_enum, conflicts_with_all = &["flame", "cargo-run"])]
+    per_node_binary: Option<InstallMode>,
+
+    /// Instead of launching the nodes, write a docker-compose file describing an equivalent network
+    /// of --num-nodes containerised nodes to this path. Nodes get static IPs on their own compose
+    /// network, so --ip, --local, --addr-map and --behind-nat don't apply.
+    #[clap(long, conflicts_with = "add-nodes-to-existing-network", value_parser)]
+    emit_compose: Option<PathBuf>,
+
+    /// Image of the containers in the --emit-compose file, which must have `sn_node` on its PATH.
+    #[clap(
+        long,
+        default_value = "sn_node:latest",
+        requires = "emit-compose",
+        value_parser
+    )]
+    compose_image: String,
+
+    /// Instead of launching the nodes, write a systemd service unit for each of them to this dir,
+    /// running the node with the command, args and env the launch would use and restarting it on
+    /// failure, plus an `sn-testnet.target` grouping them. Units are named after the nodes, e.g.
+    /// `sn-node-genesis.service`, and the other nodes are started after genesis.
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "add-nodes-to-existing-network",
+            "emit-compose",
+            "genesis-from",
+            "genesis-snapshot",
+            "behind-nat",
+            "per-node-binary",
+            "flame",
+            "detach",
+            "statsd",
+            "tag-nodes",
+        ],
+        value_parser
+    )]
+    emit_systemd: Option<PathBuf>,
+
+    /// Write a shell script reproducing this launch to this path: the command each node was
+    /// launched with, including its final args and env, and the waits in between.
+    #[clap(long, value_parser)]
+    emit_script: Option<PathBuf>,
+
+    /// Raise the open files limit the nodes inherit (`ulimit -n`) if it's estimated to be too low
+    /// for the network's size, up to the hard limit unless run with the privileges to raise that
+    /// too. Otherwise a low limit is only warned about (Unix only).
+    #[clap(long, value_parser)]
+    auto_raise_nofile: bool,
+
+    /// Kill the nodes launched so far, genesis last, if the tool is interrupted with Ctrl-C while
+    /// it's running, rather than leaving them running. Once the tool has exited, the nodes are
+    /// left running as usual.
+    #[clap(long, conflicts_with_all = &["churn-add", "detach", "emit-compose", "emit-systemd"], value_parser)]
+    kill_on_interrupt: bool,
+
+    /// Once launched, keep running until all the nodes have exited, reporting each node which
+    /// does, rather than leaving them running. Ctrl-C then kills the nodes still running, waiting
+    /// up to --shutdown-timeout-msec for them to exit.
+    #[clap(
+        long,
+        conflicts_with_all = &["churn-add", "detach", "dry-run", "kill-on-interrupt"],
+        value_parser
+    )]
+    wait: bool,
+
+    /// Once launched, show a live dashboard of the nodes with keybindings to kill or restart them.
+    #[cfg(feature = "tui")]
+    #[clap(long, conflicts_with = "wait", value_parser)]
+    tui: bool,
+}
+
+/// Addresses of the nodes of a network launched with [`Launch::run_network`].
+#[derive(Clone, Debug)]
+pub struct LaunchedNetwork {
+    /// Address the genesis node is bound to.
+    pub genesis_addr: SocketAddr,
+    /// Addresses the nodes launched after genesis are bound to, in launch order.
+    pub node_addrs: Vec<SocketAddr>,
+}
+
+/// A node process spawned by [`Launch::spawn_network`], left running for the caller to manage.
+#[derive(Debug)]
+pub struct NodeProcess {
+    /// Name of the node, which is also the name of its dir.
+    pub name: String,
+    /// Dir the node keeps its data and logs in.
+    pub dir: PathBuf,
+    /// Address the node is bound to, if it had to be discovered for the launch.
+    pub addr: Option<SocketAddr>,
+    /// The node's process, to `wait()` for or `kill()`.
+    pub child: Child,
+}
+
+/// A node spawned by the launcher, along with the command used to spawn it.
+struct LaunchedNode<'a> {
+    idx: usize,
+    name: String,
+    cmd: NodeCmd<'a>,
+    child: Child,
+    // only discovered when needed
+    addr: Option<SocketAddr>,
 }
 
 impl Launch {
+    /// The command line `args` with the flags of the --profile and the options of the --config
+    /// file given in them, if any, inserted in front, for those given explicitly to override them
+    /// once parsed, and the config file's to override the profile's.
+    pub fn expand_args<I, T>(args: I) -> Result<Vec<OsString>>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let args = config::expand(args.into_iter().map(Into::into).collect())?;
+        Ok(profiles::expand(args))
+    }
+
     /// Launch a network with these arguments.
     pub fn run(&self) -> Result<()> {
-        let mut node_cmd = self.common.node_cmd()?;
+        let launched = self.launch(None)?;
+        self.attend(launched)
+    }
+
+    /// Launch a network with these arguments, returning the addresses its nodes are bound to, e.g.
+    /// for a test harness to connect a client to it.
+    ///
+    /// Nodes bind to ephemeral ports, so each node's address is read back from its logs once it's
+    /// launched, polling for it for a few seconds. The launch fails if any launched node's
+    /// address, or genesis' address when adding nodes to an existing network, isn't found.
+    pub fn run_network(&self) -> Result<LaunchedNetwork> {
+        // a hook makes the launch discover every node's address
+        let launched = self.launch(Some(&mut |_, _, _| {}))?;
+        let addrs: Vec<_> = launched.iter().map(|node| (node.idx, node.addr)).collect();
+        self.attend(launched)?;
+
+        let mut genesis_addr = None;
+        let mut node_addrs = vec![];
+        for (idx, addr) in addrs {
+            let addr = addr.ok_or_else(|| {
+                eyre!(
+                    "Could not discover the address of node {}",
+                    self.naming.node_name(idx)
+                )
+            })?;
+            if idx == GENESIS_IDX {
+                genesis_addr = Some(addr);
+            } else {
+                node_addrs.push(addr);
+            }
+        }
+        let genesis_addr = match genesis_addr {
+            Some(addr) => addr,
+            None => address::discover_node_addr(&self.genesis_dir(), NODE_ADDRESS_TIMEOUT)?
+                .ok_or_else(|| eyre!("Could not discover the address of genesis"))?,
+        };
+
+        Ok(LaunchedNetwork {
+            genesis_addr,
+            node_addrs,
+        })
+    }
+
+    /// Launch a network with these arguments, calling `on_node_address` with each node's index,
+    /// name and bound address as soon as the node reports it in its logs.
+    ///
+    /// Nodes whose address can't be discovered within a few seconds of launching are logged and
+    /// skipped rather than failing the launch.
+    pub fn run_with_address_hook<F>(&self, mut on_node_address: F) -> Result<()>
+    where
+        F: FnMut(usize, &str, SocketAddr),
+    {
+        let launched = self.launch(Some(&mut on_node_address))?;
+        self.attend(launched)
+    }
+
+    /// Launch a network with these arguments, handing back the process of each node launched, in
+    /// launch order, for the caller to monitor, wait for or kill. --tui isn't shown.
+    pub fn spawn_network(&self) -> Result<Vec<NodeProcess>> {
+        let launched = self.launch(None)?;
+        Ok(launched
+            .into_iter()
+            .map(|node| NodeProcess {
+                dir: self.nodes_dir.join(&node.name),
+                name: node.name,
+                addr: node.addr,
+                child: node.child,
+            })
+            .collect())
+    }
+
+    /// Kill the `nodes` of a network launched with [`Launch::spawn_network`], genesis last, then
+    /// wait up to --shutdown-timeout-msec for them to exit, failing with the names of the nodes
+    /// which are still running.
+    pub fn shut_down(&self, nodes: &mut [NodeProcess]) -> Result<()> {
+        process::kill_children(
+            nodes
+                .iter_mut()
+                .rev()
+                .map(|node| (node.name.as_str(), &mut node.child)),
+            Duration::from_millis(self.shutdown_timeout_msec),
+        )
+    }
+
+    /// Add `count` nodes to the network launched with these arguments, as `--add` would, joining
+    /// with genesis' network contacts and numbered after the highest existing node, returning
+    /// their processes in launch order like [`Launch::spawn_network`].
+    ///
+    /// Options which only apply to launching a whole network, e.g. --genesis-snapshot or
+    /// --kill-existing, are ignored, as are --kill-on-interrupt and --wait, which are up to the
+    /// caller managing the processes.
+    pub fn add_nodes(&self, count: usize) -> Result<Vec<NodeProcess>> {
+        if count == 0 {
+            return Ok(vec![]);
+        }
+
+        let adding = Self {
+            add_nodes_to_existing_network: true,
+            num_nodes: count,
+            grow: None,
+            churn_add: None,
+            kill_existing: false,
+            genesis_snapshot: None,
+            genesis_from: None,
+            test_genesis_resilience: false,
+            emit_compose: None,
+            emit_systemd: None,
+            kill_on_interrupt: false,
+            wait: false,
+            ..self.clone()
+        };
+        adding.spawn_network()
+    }
+
+    /// Launch the network, returning the nodes launched.
+    fn launch(
+        &self,
+        on_node_address: Option<AddressHook<'_, '_>>,
+    ) -> Result<Vec<LaunchedNode<'_>>> {
+        if self.list_profiles {
+            profiles::print_list();
+            return Ok(vec![]);
+        }
+
+        let mut phases = timings::Phases::start();
+        let launched = self.launch_nodes(&mut phases, on_node_address);
+        if let Some(statsd) = self.statsd {
+            statsd::send_launch_metrics(
+                statsd,
+                &self.statsd_prefix,
+                &phases,
+                launched.as_ref().map(Vec::len).ok(),
+            );
+        }
+        let launched = launched?;
+
+        phases.log_summary();
+        for node in &launched {
+            let reports = node.cmd.sanitizer_reports(&self.nodes_dir.join(&node.name));
+            if !reports.is_empty() {
+                warn!(
+                    "{} has left sanitizer reports: {}",
+                    node.name,
+                    cmd::display_paths(&reports)
+                );
+            }
+        }
+        info!("Done!");
+
+        Ok(launched)
+    }
+
+    /// Show the --tui dashboard of the launched nodes, or wait for them to exit with --wait, if
+    /// asked to, before leaving them running.
+    fn attend(&self, mut launched: Vec<LaunchedNode>) -> Result<()> {
+        #[cfg(feature = "tui")]
+        if self.tui {
+            return tui::run(&self.nodes_dir, launched);
+        }
+
+        if self.wait && !launched.is_empty() {
+            self.wait_for_nodes(&mut launched)?;
+        }
+
+        Ok(())
+    }
+
+    /// Block until all the `launched` nodes have exited, reporting each which does, or until
+    /// interrupted with Ctrl-C, killing those still running, for --wait. Any latency injected for
+    /// the network is removed then.
+    fn wait_for_nodes(&self, launched: &mut [LaunchedNode]) -> Result<()> {
+        let waited = self.wait_for_exits(launched);
+        if let Err(error) = netem::remove_loopback_delay(&self.nodes_dir) {
+            warn!("{:?}", error);
+        }
+        waited
+    }
+
+    /// The wait of [`Launch::wait_for_nodes`], without its cleanup.
+    fn wait_for_exits(&self, launched: &mut [LaunchedNode]) -> Result<()> {
+        let stop = interrupt::stop_on_ctrl_c()?;
+        exits::watch()?;
+        info!(
+            "Waiting for the {} nodes launched to exit, press Ctrl-C to stop them",
+            launched.len()
+        );
+
+        let mut running: Vec<_> = launched.iter_mut().collect();
+        loop {
+            if stop.load(Ordering::SeqCst) {
+                info!(
+                    "Interrupted, stopping the {} nodes still running",
+                    running.len()
+                );
+                // genesis, always launched first, is stopped last
+                return process::kill_children(
+                    running
+                        .into_iter()
+                        .rev()
+                        .map(|node| (node.name.as_str(), &mut node.child)),
+                    Duration::from_millis(self.shutdown_timeout_msec),
+                );
+            }
+
+            running.retain_mut(|node| match node.child.try_wait() {
+                Ok(Some(status)) => {
+                    warn!("{} exited ({})", node.name, status);
+                    let node_dir = self.nodes_dir.join(&node.name);
+                    if let Err(error) = Restarts::load(&node_dir).record_exit(&node_dir, status) {
+                        warn!("{:?}", error);
+                    }
+                    false
+                }
+                Ok(None) => true,
+                Err(error) => {
+                    warn!("Could not wait for {} any more: {:?}", node.name, error);
+                    false
+                }
+            });
+            if running.is_empty() {
+                info!("All the nodes launched have exited");
+                return Ok(());
+            }
+
+            exits::sleep_until_exited(WAIT_POLL_INTERVAL, &stop);
+        }
+    }
+
+    /// Launch the nodes, timing the launch's phases into `phases`.
+    fn launch_nodes(
+        &self,
+        phases: &mut timings::Phases,
+        mut on_node_address: Option<AddressHook<'_, '_>>,
+    ) -> Result<Vec<LaunchedNode<'_>>> {
+        if self.inject_latency.is_some() {
+            netem::ensure_supported()?;
+        }
+        if let Some(ip) = self
+            .ip
+            .iter()
+            .find(|ip| self.common.ipv6 && ip.parse::<Ipv4Addr>().is_ok())
+        {
+            return Err(eyre!("--ipv6 can't be combined with the IPv4 --ip {}", ip));
+        }
+        if self.ip.len() > 1 {
+            for ip in &self.ip {
+                UdpSocket::bind(address::with_port(ip, 0)).wrap_err_with(|| {
+                    format!("Can't launch nodes on --ip {ip}, which may need to be added as an alias of the loopback interface")
+                })?;
+            }
+            if self.behind_nat.is_some() {
+                return Err(eyre!(
+                    "--behind-nat can't be combined with more than one --ip"
+                ));
+            }
+        }
+        if self.common.ipv6 {
+            UdpSocket::bind((Ipv6Addr::LOCALHOST, 0)).wrap_err(
+                "--ipv6 requires the IPv6 loopback address ::1, which isn't available on this host",
+            )?;
+        }
+        if self.verify_after.is_some()
+            && self.verify.contains(&verify::Check::Smoke)
+            && self.verify_cmd.is_none()
+        {
+            return Err(eyre!("The `smoke` check of --verify requires --verify-cmd"));
+        }
+
+        let last_idx = if self.add_nodes_to_existing_network {
+            *self.node_ids()?.end()
+        } else {
+            self.num_nodes
+        };
+        if last_idx > MAX_NODES_WITHOUT_FORCE && !self.force {
+            return Err(eyre!(
+                "Refusing to launch a network of {} nodes, more than the {} allowed without --force as each node is a process of its own",
+                last_idx,
+                MAX_NODES_WITHOUT_FORCE
+            ));
+        }
+        if let Some(base_port) = self.base_port {
+            if self.local_ip(GENESIS_IDX).is_none() {
+                return Err(eyre!("--base-port requires either --ip or --local"));
+            }
+            if usize::from(base_port) + last_idx - GENESIS_IDX > usize::from(u16::MAX) {
+                return Err(eyre!(
+                    "--base-port {} leaves no port for node #{}",
+                    base_port,
+                    last_idx
+                ));
+            }
+        }
+        self.naming.validate(last_idx)?;
+        self.check_nofile_limit(last_idx)?;
+        if let Some(launch_order) = &self.launch_order {
+            // checked up front, so an invalid order doesn't fail the launch halfway through
+            let first_idx = if self.add_nodes_to_existing_network {
+                *self.node_ids()?.start()
+            } else {
+                GENESIS_IDX + 1
+            };
+            let _ = launch_order.schedule(&(first_idx..=last_idx))?;
+        }
+
+        if self.kill_existing {
+            self.kill_existing_network()?;
+        }
+        // only real launches, which write into the nodes dir anyway, are logged there
+        if !self.common.dry_run && self.emit_compose.is_none() && self.emit_systemd.is_none() {
+            launch_log::start(&self.nodes_dir);
+            debug!(
+                "Launching with args {:?}",
+                env::args_os().skip(1).collect::<Vec<_>>()
+            );
+        }
+
+        let seed = self.seed.unwrap_or_else(intervals::random_seed);
+        let first_launched_idx = if self.add_nodes_to_existing_network {
+            *self.node_ids()?.start()
+        } else {
+            GENESIS_IDX
+        };
+        let capacities = capacity::assign(
+            self.capacities.as_ref(),
+            self.capacity_dist.as_ref(),
+            first_launched_idx..=last_idx,
+            seed,
+        )?;
+
+        self.check_disk_space(if self.add_nodes_to_existing_network {
+            self.node_ids()?.count()
+        } else {
+            self.num_nodes
+        })?;
+
+        let mut node_cmd = phases.time("binary resolution and version probe", || {
+            self.common.node_cmd()
+        })?;
+        node_cmd.set_retry_counter(phases.retry_counter());
+        if self.kill_on_interrupt {
+            node_cmd.set_spawned(interrupt::Spawned::kill_on_ctrl_c()?);
+        }
+        if let Some(restart_node_path) = &self.restart_node_path {
+            let mut binaries = vec![(
+                restart_node_path.as_path(),
+                "all nodes after the rolling restart".to_string(),
+            )];
+            // with `cargo` the nodes aren't run from a prebuilt binary
+            if !self.common.cargo_run && !node_cmd.gen_flamegraph() {
+                let first_idx = if self.add_nodes_to_existing_network {
+                    *self.node_ids()?.start()
+                } else {
+                    GENESIS_IDX
+                };
+                binaries.insert(
+                    0,
+                    (
+                        node_cmd.path(),
+                        format!("nodes {first_idx}-{last_idx} at launch"),
+                    ),
+                );
+            }
+            self.check_binaries(&binaries)?;
+        }
+        if !self.common.dry_run && self.emit_compose.is_none() && self.emit_systemd.is_none() {
+            list::record_version(
+                &self.nodes_dir,
+                &node_cmd.version()?,
+                !self.add_nodes_to_existing_network,
+            )?;
+        }
+        node_cmd.set_install_mode(self.per_node_binary);
+        node_cmd.set_stdout_to_stderr(self.logs_to_stderr());
+        if self.summary_only {
+            node_cmd.set_output_to_files(true);
+        }
+
+        if let Some(transport) = self.transport {
+            node_cmd.ensure_supports("--transport")?;
+            node_cmd.push_arg("--transport");
+            node_cmd.push_arg(transport.as_arg());
+        }
+
+        let is_quic = self.transport != Some(Transport::Tcp);
+
+        if let Some(idle) = self.idle_timeout_msec {
+            if is_quic {
+                node_cmd.push_arg("--idle-timeout-msec");
+                node_cmd.push_arg(idle.to_string());
+            } else {
+                self.warn_or_fail(
+                    "Ignoring --idle-timeout-msec as it only applies to the QUIC transport",
+                )?;
+            }
+        }
+
+        if let Some(keep_alive_interval_msec) = self.keep_alive_interval_msec {
+            if is_quic {
+                node_cmd.push_arg("--keep-alive-interval-msec");
+                node_cmd.push_arg(keep_alive_interval_msec.to_string());
+            } else {
+                self.warn_or_fail(
+                    "Ignoring --keep-alive-interval-msec as it only applies to the QUIC transport",
+                )?;
+            }
+        }
+
+        if let Some(max_connections) = self.max_connections {
+            if node_cmd.supports_flag(&self.max_connections_flag)? {
+                node_cmd.push_arg(self.max_connections_flag.as_str());
+                node_cmd.push_arg(max_connections.to_string());
+            } else {
+                self.warn_or_fail(format!(
+                    "Ignoring --max-connections as the sn_node binary doesn't support {}, see --max-connections-flag",
+                    self.max_connections_flag
+                ))?;
+            }
+        }
+
+        if let Some(compose_file) = &self.emit_compose {
+            compose::write_compose_file(
+                compose_file,
+                &self.compose_image,
+                &node_cmd,
+                &self.naming,
+                self.num_nodes,
+            )?;
+            return Ok(vec![]);
+        }
+
+        let addr_map = match &self.addr_map {
+            Some(path) => {
+                let mut addr_map = addr_map::read(path)?;
+                self.check_addr_map(&addr_map)?;
+                addr_map::ensure_free_ports(&mut addr_map, self.port_search)?;
+                addr_map
+            }
+            None => BTreeMap::new(),
+        };
+
+        if let Some(systemd_dir) = &self.emit_systemd {
+            let mut units = vec![(
+                self.naming.node_name(GENESIS_IDX),
+                self.genesis_cmd(
+                    &capacity::with_capacity(
+                        &node_cmd,
+                        capacities.get(&GENESIS_IDX),
+                        &self.capacity_flag,
+                    ),
+                    addr_map.get(&GENESIS_IDX),
+                ),
+            )];
+            let mut joining_cmd = node_cmd.clone();
+            joining_cmd.push_arg("--network-contacts-file");
+            joining_cmd.push_arg(self.genesis_dir().join(GENESIS_CONTACTS_FILE));
+            for idx in GENESIS_IDX + 1..=self.num_nodes {
+                units.push((
+                    self.naming.node_name(idx),
+                    capacity::with_capacity(
+                        &self.with_node_addrs(&joining_cmd, idx, addr_map.get(&idx)),
+                        capacities.get(&idx),
+                        &self.capacity_flag,
+                    ),
+                ));
+            }
+            systemd::write_units(systemd_dir, &units, &self.nodes_dir)?;
+            return Ok(vec![]);
+        }
+
+        if self.common.check_node_flags {
+            let mut extra_flags = vec!["--first", "--network-contacts-file"];
+            if !capacities.is_empty() {
+                extra_flags.push(&self.capacity_flag);
+            }
+            if self.local_ip(GENESIS_IDX).is_some() || !addr_map.is_empty() {
+                extra_flags.push("--local-addr");
+            }
+            if self.behind_nat.is_some() || addr_map.values().any(|addrs| addrs.public.is_some()) {
+                extra_flags.push("--public-addr");
+            }
+            node_cmd.check_flags(&extra_flags)?;
+        }
+
+        debug!("Network size: {} nodes", self.num_nodes);
+
+        let mut intervals = intervals::Intervals::new(self.interval, self.interval_jitter, seed);
+
+        let genesis_contacts_filepath = self.genesis_dir().join(GENESIS_CONTACTS_FILE);
+
+        let node_tags = if self.tag_nodes {
+            Some(tags::NodeTags::load(
+                &self.nodes_dir,
+                self.add_nodes_to_existing_network,
+                self.common.dry_run,
+                &node_cmd,
+            )?)
+        } else {
+            None
+        };
+
+        // nothing is launched by a dry run, so there's no memory to wait for
+        let mut memory_guard =
+            self.min_free_mem
+                .filter(|_| !self.common.dry_run)
+                .map(|min_free_mib| {
+                    memory::MemoryGuard::new(
+                        min_free_mib,
+                        Duration::from_secs(self.min_free_mem_timeout),
+                    )
+                });
+
+        let mut readiness = self.readiness_strategy(&mut intervals);
+        let mut launched = vec![];
+        if !self.add_nodes_to_existing_network && self.common.dry_run {
+            self.genesis_cmd(
+                &capacity::with_capacity(
+                    &with_node_tag(&node_cmd, node_tags.as_ref(), GENESIS_IDX),
+                    capacities.get(&GENESIS_IDX),
+                    &self.capacity_flag,
+                ),
+                addr_map.get(&GENESIS_IDX),
+            )
+            .print_invocation(&self.naming.node_name(GENESIS_IDX), &self.nodes_dir);
+        } else if !self.add_nodes_to_existing_network {
+            let genesis_spawned_at = SystemTime::now();
+            let genesis = phases.time("genesis launch", || {
+                self.run_genesis(
+                    &capacity::with_capacity(
+                        &with_node_tag(&node_cmd, node_tags.as_ref(), GENESIS_IDX),
+                        capacities.get(&GENESIS_IDX),
+                        &self.capacity_flag,
+                    ),
+                    addr_map.get(&GENESIS_IDX),
+                    on_node_address.as_deref_mut(),
+                )
+            })?;
+            let genesis_span = node_span(&genesis.name, GENESIS_IDX);
+            phases.time("genesis readiness wait", || {
+                genesis_span.in_scope(|| match &self.genesis_ready_cmd {
+                    Some(ready_cmd) => self.wait_for_genesis(ready_cmd, &genesis_contacts_filepath),
+                    None => readiness.wait_until_ready(&genesis, &self.genesis_dir()),
+                })
+            })?;
+            phases.time("genesis contacts wait", || {
+                self.wait_for_genesis_contacts(&genesis_contacts_filepath)
+            })?;
+            if let Some(addr) = genesis
+                .addr
+                .filter(|addr| self.common.ipv6 && addr.is_ipv4())
+            {
+                self.warn_or_fail(format!(
+                    "Genesis is listening on the IPv4 address {addr} despite --ipv6, the sn_node binary may not support IPv6"
+                ))?;
+            }
+            launched.push(genesis);
+            if let Some(appeared) =
+                timings::written_after(&genesis_contacts_filepath, genesis_spawned_at)
+            {
+                phases.record("contacts file appearance", appeared);
+            }
+
+            debug!("Genesis wait over...");
+        }
+
+        let node_ids = if self.common.dry_run && !self.add_nodes_to_existing_network {
+            // genesis was only printed, so it has no dir to count the nodes from
+            GENESIS_IDX + 1..=self.num_nodes
+        } else {
+            self.node_ids()?
+        };
+        if let Some(per_minute) = self.churn_add {
+            node_cmd.push_arg("--network-contacts-file");
+            node_cmd.push_arg(genesis_contacts_filepath.clone());
+
+            let nodes_started = Instant::now();
+            self.churn_add(
+                &node_cmd,
+                *node_ids.start(),
+                per_minute,
+                &addr_map,
+                node_tags.as_ref(),
+                memory_guard.as_mut(),
+                on_node_address.as_deref_mut(),
+                &mut launched,
+            )?;
+            phases.record("node launches", nodes_started.elapsed());
+        } else if !node_ids.is_empty() {
+            node_cmd.push_arg("--network-contacts-file");
+            node_cmd.push_arg(genesis_contacts_filepath.clone());
+
+            debug!(
+                "Common node args for launching the network: {:?}",
+                node_cmd.args()
+            );
+            info!("Launching nodes {:?}", node_ids);
+
+            let mut launch_order = match &self.launch_order {
+                Some(launch_order) => launch_order.schedule(&node_ids)?,
+                None => node_ids.clone().collect(),
+            };
+            if self.shuffle_order {
+                order::shuffle(&mut launch_order, seed);
+                info!(
+                    "Launching the nodes in the shuffled order {:?} with seed {} (pass --seed {} to reproduce it)",
+                    launch_order, seed, seed
+                );
+            }
+            let (reachable_cmd, nat_ids) = self.nat_setup(&node_cmd, &node_ids)?;
+            let nodes_started = Instant::now();
+            let batch_size = self.parallel.unwrap_or(1) as usize;
+            for batch in launch_order.chunks(batch_size) {
+                let mut cmds = vec![];
+                for &i in batch {
+                    let cmd = if nat_ids.contains(&i) {
+                        debug!("Node #{} will be behind NAT", i);
+                        &node_cmd
+                    } else {
+                        &reachable_cmd
+                    };
+                    if let Some(memory_guard) = &mut memory_guard {
+                        memory_guard.wait_for_free_memory(&self.naming.node_name(i))?;
+                    }
+                    let cmd = capacity::with_capacity(
+                        &self.with_node_addrs(
+                            &with_node_tag(cmd, node_tags.as_ref(), i),
+                            i,
+                            addr_map.get(&i),
+                        ),
+                        capacities.get(&i),
+                        &self.capacity_flag,
+                    );
+                    cmds.push((i, cmd));
+                }
+
+                if self.common.dry_run {
+                    for (i, cmd) in &cmds {
+                        cmd.print_invocation(&self.naming.node_name(*i), &self.nodes_dir);
+                    }
+                    continue;
+                }
+                let nodes = match &cmds[..] {
+                    [(i, cmd)] => {
+                        let node_started = Instant::now();
+                        let node = self.run_node(cmd, *i, on_node_address.as_deref_mut())?;
+                        phases.record_node_launch(node_started.elapsed());
+                        vec![node]
+                    }
+                    _ => self.run_nodes_together(cmds, phases, on_node_address.as_deref_mut())?,
+                };
+                for (n, node) in nodes.into_iter().enumerate() {
+                    // the interval is waited once per batch
+                    if n == 0 || self.readiness != Readiness::Sleep {
+                        node_span(&node.name, node.idx).in_scope(|| {
+                            readiness.wait_until_ready(&node, &self.nodes_dir.join(&node.name))
+                        })?;
+                    }
+                    launched.push(node);
+                }
+            }
+            phases.record("node launches", nodes_started.elapsed());
+        }
+
+        if self.common.dry_run {
+            return Ok(launched);
+        }
+
+        if self.check_distinct_addrs {
+            self.check_distinct_addrs(&launched)?;
+        }
+
+        // done with the intervals the `sleep` strategy waited for
+        drop(readiness);
+
+        if let Some(script_file) = &self.emit_script {
+            let genesis_wait = match &self.genesis_ready_cmd {
+                Some(command) => script::GenesisWait::ReadyCmd {
+                    command,
+                    contacts: &genesis_contacts_filepath,
+                },
+                None => script::GenesisWait::Sleep,
+            };
+            script::write_script(
+                script_file,
+                &launched,
+                &self.nodes_dir,
+                intervals.chosen(),
+                genesis_wait,
+            )?;
+        }
+
+        if let Some(delay_ms) = self.inject_latency {
+            netem::apply_loopback_delay(delay_ms, &self.nodes_dir)?;
+        }
+
+        if let Some(delay_secs) = self.contacts_copy_delay {
+            info!(
+                "Waiting {}s before copying the network contacts",
+                delay_secs
+            );
+            phases.time("contacts copy delay", || {
+                thread::sleep(Duration::from_secs(delay_secs))
+            });
+        }
+        if let Some(quiet_secs) = self.contacts_quiet_secs {
+            info!(
+                "Waiting for the network contacts to be unchanged for {}s before copying them",
+                quiet_secs
+            );
+            let settled = phases.time("contacts settling", || {
+                timings::wait_until_quiet(
+                    &genesis_contacts_filepath,
+                    Duration::from_secs(quiet_secs),
+                    Duration::from_secs(self.contacts_quiet_timeout),
+                )
+            });
+            if !settled {
+                warn!(
+                    "The network contacts were still changing after {}s, copying them anyway",
+                    self.contacts_quiet_timeout
+                );
+            }
+        }
+
+        // Let's copy the genesis' section_tree file to the locations for clients to use.
+        // The network is already up at this point, so failing to do so isn't fatal unless required.
+        let mut contacts_files = vec![];
+        let copied = if self.no_client_contacts {
+            info!(
+                "Not copying the network contacts for clients, which can be pointed at {}",
+                genesis_contacts_filepath.display()
+            );
+            Ok(())
+        } else {
+            phases.time("contacts copy", || {
+                self.copy_network_contacts(
+                    &genesis_contacts_filepath,
+                    &launched,
+                    &mut contacts_files,
+                )
+            })
+        };
+        if let Err(error) = copied {
+            if self.require_contacts_copy {
+                return Err(error);
+            }
+            self.warn_or_fail(format!(
+                "The network is up, but its contacts couldn't be copied for clients to use: {error:?}"
+            ))?;
+            warn!(
+                "Copy {} manually for local clients to bootstrap to the network",
+                genesis_contacts_filepath.display()
+            );
+        }
+
+        if let Some(hosts_file) = &self.hosts_file {
+            output::write_hosts_file(hosts_file, &launched)?;
+        }
+
+        if let Some(addrs_json) = &self.addrs_json {
+            output::write_addrs_json(addrs_json, &launched)?;
+        }
+
+        if self.print_join_cmd {
+            self.print_join_cmd(&genesis_contacts_filepath)?;
+        }
+
+        if self.export_env || self.summary_only {
+            self.print_env_exports(&genesis_contacts_filepath)?;
+        }
+
+        if self.rolling_restart {
+            restart::rolling_restart(
+                &mut launched,
+                &self.nodes_dir,
+                self.restart_node_path.as_deref(),
+                Duration::from_secs(self.restart_settle_secs),
+                self.restart_genesis_first,
+                |node, logged_before| self.wait_for_rejoin(node, logged_before),
+            )?;
+            // all the nodes run the binary they were restarted with now
+            if let Some(node) = launched
+                .first()
+                .filter(|_| self.restart_node_path.is_some())
+            {
+                if let Err(error) = node
+                    .cmd
+                    .version()
+                    .and_then(|version| list::record_version(&self.nodes_dir, &version, true))
+                {
+                    warn!("{:?}", error);
+                }
+            }
+        }
+
+        if let Some(settle_secs) = self.verify_after {
+            verify::Verification {
+                checks: &self.verify,
+                settle: Duration::from_secs(settle_secs),
+                tcp: self.transport == Some(Transport::Tcp),
+                smoke_cmd: self.verify_cmd.as_deref(),
+                nodes_dir: &self.nodes_dir,
+                genesis_contacts: &genesis_contacts_filepath,
+            }
+            .run(&mut launched)?;
+        }
+
+        if self.test_genesis_resilience {
+            resilience::test_genesis_resilience(
+                &mut launched,
+                Duration::from_secs(self.resilience_settle_secs),
+                Duration::from_millis(self.shutdown_timeout_msec),
+                self.transport == Some(Transport::Tcp),
+            )?;
+        }
+
+        // last, so it's only written for a network which is up, and passed any checks asked for
+        if let Some(summary_json) = &self.summary_json {
+            output::write_summary_json(
+                summary_json,
+                &launched,
+                &self.nodes_dir,
+                &contacts_files,
+                node_tags.as_ref(),
+            )?;
+        }
+
+        Ok(launched)
+    }
+
+    /// Probe each of the `binaries`, along with which nodes run it, logging which versions the
+    /// nodes will run and warning when they may not be compatible.
+    fn check_binaries(&self, binaries: &[(&Path, String)]) -> Result<()> {
+        let paths: Vec<_> = binaries.iter().map(|(path, _)| *path).collect();
+        let mut versions = vec![];
+        let mut failures = vec![];
+        let mut unsupported = vec![];
+        for (binary, result) in probe::probe_binaries(&paths) {
+            match result {
+                Ok(info) => {
+                    debug!("{} supports {} flags", binary.display(), info.flags.len());
+                    if !self.common.skip_version_check {
+                        if let Err(error) = probe::ensure_supported_version(&info.version) {
+                            unsupported.push(format!("{}: {}", binary.display(), error));
+                        }
+                    }
+                    versions.push((binary, info.version));
+                }
+                Err(error) => failures.push(format!("{}: {}", binary.display(), error)),
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(eyre!(
+                "Could not probe the sn_node binaries:\n{}",
+                failures.join("\n")
+            ));
+        }
+        if !unsupported.is_empty() {
+            return Err(eyre!(
+                "Unsupported sn_node binaries:\n{}",
+                unsupported.join("\n")
+            ));
+        }
+
+        info!("sn_node versions:");
+        for (path, nodes) in binaries {
+            let version = versions
+                .iter()
+                .find(|(binary, _)| binary == path)
+                .map_or("?", |(_, version)| version.as_str());
+            info!("  {} for {} ({})", version, nodes, path.display());
+        }
+
+        for (idx, (binary, version)) in versions.iter().enumerate() {
+            for (other_binary, other_version) in &versions[idx + 1..] {
+                match (probe::semver(version), probe::semver(other_version)) {
+                    (Some(semver), Some(other_semver))
+                        if !probe::compatible(semver, other_semver) =>
+                    {
+                        self.warn_or_fail(format!(
+                            "{} ({}) and {} ({}) may not be compatible",
+                            binary.display(),
+                            version,
+                            other_binary.display(),
+                            other_version
+                        ))?
+                    }
+                    (Some(_), Some(_)) => {}
+                    _ if version != other_version => self.warn_or_fail(format!(
+                        "Could not tell whether '{version}' and '{other_version}' are compatible"
+                    ))?,
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Warn, or fail with --strict, if the open files limit the nodes inherit is too low for a
+    /// network of `network_size` nodes, unless it could be raised with --auto-raise-nofile.
+    fn check_nofile_limit(&self, network_size: usize) -> Result<()> {
+        let (mut soft, hard) = match nofile::limits()? {
+            Some(limits) => limits,
+            None => return Ok(()),
+        };
+        let needed = nofile::needed(network_size);
+        debug!(
+            "Open files limit is {} (hard: {}), {} estimated to be needed per node",
+            soft, hard, needed
+        );
+        if soft >= needed {
+            return Ok(());
+        }
+
+        if self.auto_raise_nofile {
+            match nofile::raise(needed) {
+                Ok(()) => {
+                    info!(
+                        "Raised the open files limit from {} to {} for the nodes",
+                        soft, needed
+                    );
+                    return Ok(());
+                }
+                Err(error) => warn!("{:?}", error),
+            }
+            // without the privileges to raise the hard limit, get as close as it allows
+            if hard > soft && nofile::raise(hard).is_ok() {
+                info!("Raised the open files limit from {} to {}", soft, hard);
+                soft = hard;
+            }
+        }
+
+        self.warn_or_fail(format!(
+            "The open files limit is {}, but each node of a network of {} nodes is estimated to need {}: nodes may fail to open connections or files, raise it with `ulimit -n {}`{}",
+            soft,
+            network_size,
+            needed,
+            needed,
+            if self.auto_raise_nofile || hard < needed {
+                ""
+            } else {
+                " or pass --auto-raise-nofile"
+            }
+        ))
+    }
+
+    /// Check there's enough disk space for `launching` nodes according to --disk-check.
+    fn check_disk_space(&self, launching: usize) -> Result<()> {
+        if self.disk_check == DiskCheck::Off {
+            return Ok(());
+        }
+
+        let available = match disk::available_space(&self.nodes_dir)? {
+            Some(available) => available,
+            None => {
+                return self
+                    .warn_or_fail("Checking the free disk space isn't supported on this platform");
+            }
+        };
+        const MIB: u64 = 1024 * 1024;
+        let needed = launching as u64 * self.est_node_disk_mb * MIB;
+        debug!(
+            "{} MiB of disk space available for the nodes, {} MiB estimated to be needed",
+            available / MIB,
+            needed / MIB
+        );
+        if available >= needed {
+            return Ok(());
+        }
+
+        let message = format!(
+            "Only {} MiB of disk space is available in {}, but launching {} nodes is estimated to need {} MiB ({} MiB each, see --est-node-disk-mb)",
+            available / MIB,
+            self.nodes_dir.display(),
+            launching,
+            needed / MIB,
+            self.est_node_disk_mb
+        );
+        match self.disk_check {
+            DiskCheck::Enforce => Err(eyre!(message)),
+            _ => self.warn_or_fail(message),
+        }
+    }
+
+    fn genesis_dir(&self) -> PathBuf {
+        self.nodes_dir.join(self.naming.node_name(GENESIS_IDX))
+    }
+
+    fn wait_for_genesis(&self, ready_cmd: &str, genesis_contacts_filepath: &Path) -> Result<()> {
+        info!(
+            "Waiting for genesis to be ready according to '{}'",
+            ready_cmd
+        );
+
+        let mut envs = vec![
+            ("SN_GENESIS_CONTACTS", genesis_contacts_filepath.into()),
+            ("SN_NODES_DIR", self.nodes_dir.clone().into()),
+        ];
+        let genesis_dir = self.genesis_dir();
+        if let Some(addr) = address::discover_node_addr(&genesis_dir, Duration::ZERO)? {
+            envs.push(("SN_GENESIS_ADDR", addr.to_string().into()));
+        }
+
+        readiness::wait_for_command(
+            ready_cmd,
+            &envs,
+            Duration::from_secs(self.genesis_ready_timeout),
+        )
+        .wrap_err("Genesis node didn't become ready")
+    }
+
+    /// Wait for genesis to have written its network contacts, which mustn't be empty, for the
+    /// other nodes to join with.
+    fn wait_for_genesis_contacts(&self, genesis_contacts_filepath: &Path) -> Result<()> {
+        let timeout = Duration::from_secs(self.genesis_contacts_timeout);
+        let started = Instant::now();
+        while !fs::metadata(genesis_contacts_filepath).is_ok_and(|metadata| metadata.len() > 0) {
+            if started.elapsed() >= timeout {
+                let genesis_dir = self.genesis_dir();
+                let log = logs::newest_log_file(&genesis_dir).unwrap_or(genesis_dir);
+                return Err(eyre!(
+                    "Genesis didn't write its network contacts {} within {}s, see its log at {}",
+                    genesis_contacts_filepath.display(),
+                    timeout.as_secs(),
+                    log.display()
+                ));
+            }
+            thread::sleep(CONTACTS_POLL_INTERVAL);
+        }
+        debug!(
+            "Genesis network contacts written after {:.1}s",
+            started.elapsed().as_secs_f64()
+        );
+        Ok(())
+    }
+
+    fn print_join_cmd(&self, genesis_contacts_filepath: &Path) -> Result<()> {
+        let contacts = fs::canonicalize(genesis_contacts_filepath).wrap_err_with(|| {
+            format!(
+                "Could not resolve the network contacts file {}",
+                genesis_contacts_filepath.display()
+            )
+        })?;
+
+        let mut join_cmd = format!(
+            "sn_launch_tool join --network-contacts-file {:?} --nodes-dir ./joined-node",
+            contacts
+        );
+        if let Some(node_path) = &self.common.node_path {
+            join_cmd.push_str(&format!(" --node-path {:?}", node_path));
+        }
+        if !self.ip.is_empty() {
+            if let Some(local_addr) = self.default_local_addr(GENESIS_IDX) {
+                join_cmd.push_str(&format!(" --local-addr {local_addr}"));
+            }
+        } else if self.common.is_local {
+            join_cmd.push_str(" --local");
+            if self.common.ipv6 {
+                join_cmd.push_str(" --ipv6");
+            }
+        }
+
+        println!("To add your own node to this network, run:");
+        println!("    {join_cmd}");
+
+        Ok(())
+    }
+
+    fn print_env_exports(&self, genesis_contacts_filepath: &Path) -> Result<()> {
+        let mut vars = vec![];
+        let genesis_dir = self.genesis_dir();
+        match address::discover_node_addr(&genesis_dir, Duration::ZERO)? {
+            Some(addr) => vars.push(("SN_GENESIS_ADDR", addr.to_string())),
+            None => self.warn_or_fail("Could not discover the genesis address to export it")?,
+        }
+        for (name, path) in [
+            ("SN_GENESIS_CONTACTS", genesis_contacts_filepath),
+            ("SN_NODES_DIR", self.nodes_dir.as_path()),
+        ] {
+            let path = fs::canonicalize(path)
+                .wrap_err_with(|| format!("Could not resolve {}", path.display()))?;
+            vars.push((name, path.display().to_string()));
+        }
+
+        output::print_env_exports(
+            &vars,
+            self.export_shell
+                .unwrap_or_else(ExportShell::platform_default),
+        );
+
+        Ok(())
+    }
+
+    /// Whether stdout is reserved for machine-readable output, in which case the tool's logs
+    /// should be written to stderr instead.
+    pub fn logs_to_stderr(&self) -> bool {
+        self.export_env || self.summary_only || self.addrs_json.as_deref() == Some(Path::new("-"))
+    }
+
+    /// Whether only errors should be logged, leaving the final summary as the only output.
+    pub fn errors_only(&self) -> bool {
+        self.summary_only
+    }
+
+    /// Copy the network contacts to each of the locations for clients, adding those they were
+    /// copied to to `copied`, and failing with the ones they couldn't be copied to once they've
+    /// all been tried.
+    fn copy_network_contacts(
+        &self,
+        genesis_contacts_filepath: &Path,
+        launched: &[LaunchedNode],
+        copied: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        let mut failed = vec![];
+        let mut destinations = vec![];
+        let mut client_contacts = None;
+        if !self.no_default_contacts {
+            let dir = match &self.client_contacts_dir {
+                Some(dir) => Ok(dir.clone()),
+                None => client_network_contacts_dir(),
+            };
+            match dir {
+                Ok(dir) => {
+                    client_contacts = Some(dir.join(DEFAULT_NETWORK_CONTACTS));
+                    destinations.extend(client_contacts.clone());
+                }
+                Err(error) => {
+                    warn!("{:?}", error);
+                    failed.push("the default location".to_string());
+                }
+            }
+        }
+        destinations.extend(self.contacts_output.iter().cloned());
+
+        for destination in destinations {
+            info!(
+                "Copying network contacts file to {} for clients to bootstrap to the network",
+                destination.display()
+            );
+            let backed_up = if self.no_backup || client_contacts.as_ref() != Some(&destination) {
+                Ok(())
+            } else {
+                back_up_contacts(genesis_contacts_filepath, &destination)
+            };
+            let result = backed_up
+                .and_then(|()| copy_contacts(genesis_contacts_filepath, &destination))
+                .and_then(|()| {
+                    if !self.contacts_addrs {
+                        return Ok(());
+                    }
+                    let mut addrs_path = destination.clone().into_os_string();
+                    addrs_path.push(CONTACTS_ADDRS_SUFFIX);
+                    output::write_addrs_json(Path::new(&addrs_path), launched)
+                });
+            match result {
+                Ok(()) => copied.push(destination),
+                Err(error) => {
+                    warn!("{:?}", error);
+                    failed.push(destination.display().to_string());
+                }
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!(
+                "Could not copy the network contacts to {}",
+                failed.join(", ")
+            ))
+        }
+    }
+
+    /// Stop the nodes of a previous run in the nodes dir and remove it, for --kill-existing.
+    fn kill_existing_network(&self) -> Result<()> {
+        let nodes_dir = match fs::canonicalize(&self.nodes_dir) {
+            Ok(nodes_dir) => nodes_dir,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => {
+                return Err(eyre!(error).wrap_err(format!(
+                    "Could not resolve nodes dir {}",
+                    self.nodes_dir.display()
+                )))
+            }
+        };
+        self.ensure_removable(&nodes_dir)?;
+
+        let pids = if nodes_dir.join(process::NETWORK_PIDS_FILE).is_file() {
+            process::read_network_pids(&nodes_dir)?
+        } else {
+            vec![]
+        };
+        // only processes known to be running are signalled, in case their PIDs have been reused
+        let mut stopping = vec![];
+        for (name, pid) in pids {
+            if process::is_alive(pid) != Some(true) {
+                debug!("{} (PID {}) isn't running", name, pid);
+                continue;
+            }
+            match process::terminate(pid) {
+                Ok(()) => stopping.push(pid),
+                Err(error) => debug!("Could not stop {}: {:?}", name, error),
+            }
+        }
+        let stopped = stopping.len();
+        for pid in process::wait_for_exits(&stopping, KILL_EXISTING_GRACE) {
+            if process::is_alive(pid) != Some(true) {
+                continue;
+            }
+            warn!(
+                "PID {} didn't exit within {}s, killing it",
+                pid,
+                KILL_EXISTING_GRACE.as_secs()
+            );
+            if let Err(error) = process::kill(pid) {
+                debug!("Could not kill PID {}: {:?}", pid, error);
+            }
+        }
+
+        netem::remove_loopback_delay(&nodes_dir)?;
+        fs::remove_dir_all(&nodes_dir)
+            .wrap_err_with(|| format!("Could not remove the nodes dir {}", nodes_dir.display()))?;
+        info!(
+            "Stopped {} nodes of the previous run and removed {}",
+            stopped,
+            nodes_dir.display()
+        );
+
+        Ok(())
+    }
+
+    /// Fail if removing the canonical `nodes_dir` for --kill-existing may take more than a previous
+    /// run's nodes with it.
+    fn ensure_removable(&self, nodes_dir: &Path) -> Result<()> {
+        let holds = |path: Option<PathBuf>| {
+            path.and_then(|path| fs::canonicalize(path).ok())
+                .is_some_and(|path| path.starts_with(nodes_dir))
+        };
+        if nodes_dir.parent().is_none()
+            || holds(dirs_next::home_dir())
+            || holds(env::current_dir().ok())
+            || holds(self.genesis_from.clone())
+        {
+            return Err(eyre!(
+                "Refusing to remove {} for --kill-existing, as it's the root, home or current dir, holds one of them, or holds --genesis-from",
+                nodes_dir.display()
+            ));
+        }
+
+        // anything else must look like a nodes dir, in case it was given by mistake
+        let mut entries = fs::read_dir(nodes_dir)
+            .wrap_err_with(|| format!("Could not read nodes dir {}", nodes_dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .peekable();
+        let is_empty = entries.peek().is_none();
+        let has_nodes = entries.any(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name == process::NETWORK_PIDS_FILE
+                || self.naming.node_idx(&name).is_some()
+                || naming::is_joined_node_name(&name)
+        });
+        if is_empty || has_nodes {
+            Ok(())
+        } else {
+            Err(eyre!(
+                "Refusing to remove {} for --kill-existing, as it holds neither nodes nor {}",
+                nodes_dir.display(),
+                process::NETWORK_PIDS_FILE
+            ))
+        }
+    }
+
+    /// Make the existing node data in `data_dir` genesis' data, for --genesis-from.
+    fn reuse_genesis_data(&self, data_dir: &Path) -> Result<()> {
+        if !data_dir.join(GENESIS_CONTACTS_FILE).is_file() {
+            return Err(eyre!(
+                "{} can't be relaunched as genesis, as it has no network contacts (section_tree)",
+                data_dir.display()
+            ));
+        }
+
+        let genesis_dir = self.genesis_dir();
+        let is_genesis_dir = match (fs::canonicalize(data_dir), fs::canonicalize(&genesis_dir)) {
+            (Ok(data_dir), Ok(genesis_dir)) => data_dir == genesis_dir,
+            _ => false,
+        };
+        if is_genesis_dir {
+            info!("Relaunching genesis from its existing data");
+            return Ok(());
+        }
+        if genesis_dir.exists() {
+            return Err(eyre!(
+                "Can't relaunch genesis from {} as {} already exists, remove it first",
+                data_dir.display(),
+                genesis_dir.display()
+            ));
+        }
+
+        info!(
+            "Relaunching genesis from the data of {}",
+            data_dir.display()
+        );
+        snapshot::copy_dir_all(data_dir, &genesis_dir)
+            .wrap_err("Could not copy the data to relaunch genesis from")
+    }
+
+    fn run_genesis<'a>(
+        &'a self,
+        node_cmd: &NodeCmd<'a>,
+        addrs: Option<&NodeAddrs>,
+        on_node_address: Option<AddressHook<'_, '_>>,
+    ) -> Result<LaunchedNode<'a>> {
+        let genesis_cmd = self.genesis_cmd(node_cmd, addrs);
+        if let Some(data_dir) = &self.genesis_from {
+            self.reuse_genesis_data(data_dir)?;
+        }
+
+        if let Some(snapshot_dir) = &self.genesis_snapshot {
+            snapshot::seed_genesis(snapshot_dir, &self.genesis_dir(), &node_cmd.version()?)?;
+        }
+
+        // Let's launch genesis node now
+        let name = self.naming.node_name(GENESIS_IDX);
+        let _span = node_span(&name, GENESIS_IDX).entered();
+        debug!("Launching genesis node (#{})...", GENESIS_IDX);
+        let (child, stdout_addr) = if self.genesis_addr_from_stdout {
+            // only piped for this run, as nothing would read it if genesis was run again
+            let mut scanned_cmd = genesis_cmd.clone();
+            scanned_cmd.set_pipe_stdout(true);
+            let mut child =
+                self.with_port_hint(scanned_cmd.run(&name, &self.nodes_dir), GENESIS_IDX)?;
+            let stdout_addr = child
+                .stdout
+                .take()
+                .map(|stdout| address::scan_stdout(stdout, self.logs_to_stderr()));
+            (child, stdout_addr)
+        } else {
+            let child =
+                self.with_port_hint(genesis_cmd.run(&name, &self.nodes_dir), GENESIS_IDX)?;
+            (child, None)
+        };
+        let addr =
+            self.discover_node_addr(GENESIS_IDX, &name, stdout_addr.as_ref(), on_node_address)?;
+
+        Ok(LaunchedNode {
+            idx: GENESIS_IDX,
+            name,
+            cmd: genesis_cmd,
+            child,
+            addr,
+        })
+    }
+
+    /// `node_cmd` with genesis' args: its addresses, and `--first` unless relaunched with
+    /// --genesis-from.
+    fn genesis_cmd<'a>(&self, node_cmd: &NodeCmd<'a>, addrs: Option<&NodeAddrs>) -> NodeCmd<'a> {
+        let mut genesis_cmd = node_cmd.clone();
+        let (local_addr, public_addr) = match addrs {
+            Some(addrs) => (
+                Some(addrs.local.to_string()),
+                Some(addrs.public.unwrap_or(addrs.local).to_string()),
+            ),
+            None => (
+                self.default_local_addr(GENESIS_IDX),
+                self.default_local_addr(GENESIS_IDX),
+            ),
+        };
+        if let Some(local_addr) = local_addr {
+            genesis_cmd.push_arg("--local-addr");
+            genesis_cmd.push_arg(local_addr);
+        }
+        if self.genesis_from.is_some() {
+            // restarted against its existing state, so it rejoins its own network
+            if let Some(public_addr) = addrs.and_then(|addrs| addrs.public) {
+                genesis_cmd.push_arg("--public-addr");
+                genesis_cmd.push_arg(public_addr.to_string());
+            }
+            genesis_cmd.push_arg("--network-contacts-file");
+            genesis_cmd.push_arg(self.genesis_dir().join(GENESIS_CONTACTS_FILE));
+        } else {
+            genesis_cmd.push_arg("--first");
+            // `--first` requires a public address. Port `0` means it will be the same as locally bound port.
+            if let Some(public_addr) = public_addr {
+                genesis_cmd.push_arg(public_addr);
+            }
+        }
+
+        genesis_cmd
+    }
+
+    fn run_node<'a>(
+        &'a self,
+        node_cmd: &NodeCmd<'a>,
+        node_idx: usize,
+        on_node_address: Option<AddressHook<'_, '_>>,
+    ) -> Result<LaunchedNode<'a>> {
+        let node_name = self.naming.node_name(node_idx);
+        let _span = node_span(&node_name, node_idx).entered();
+        if self.add_nodes_to_existing_network {
+            debug!("Adding node #{}...", node_idx)
+        } else {
+            debug!("Launching node #{}...", node_idx)
+        };
+        let child = self.with_port_hint(node_cmd.run(&node_name, &self.nodes_dir), node_idx)?;
+        self.launched_node(node_cmd, node_idx, node_name, child, on_node_address)
+    }
+
+    /// Launch the nodes of `cmds`, with their indices, all at once, failing with every node which
+    /// failed to start, once those which did are killed.
+    fn run_nodes_together<'a>(
+        &'a self,
+        cmds: Vec<(usize, NodeCmd<'a>)>,
+        phases: &mut timings::Phases,
+        mut on_node_address: Option<AddressHook<'_, '_>>,
+    ) -> Result<Vec<LaunchedNode<'a>>> {
+        debug!(
+            "Launching nodes {:?} together...",
+            cmds.iter().map(|(idx, _)| idx).collect::<Vec<_>>()
+        );
+        let results: Vec<_> = thread::scope(|scope| {
+            let spawns: Vec<_> = cmds
+                .iter()
+                .map(|(idx, cmd)| {
+                    let node_name = self.naming.node_name(*idx);
+                    scope.spawn(move || {
+                        let _span = node_span(&node_name, *idx).entered();
+                        let node_started = Instant::now();
+                        let child =
+                            self.with_port_hint(cmd.run(&node_name, &self.nodes_dir), *idx)?;
+                        Ok((node_name, child, node_started.elapsed()))
+                    })
+                })
+                .collect();
+            spawns
+                .into_iter()
+                .map(|spawn| {
+                    spawn
+                        .join()
+                        .unwrap_or_else(|_| Err(eyre!("Launching the node panicked")))
+                })
+                .collect()
+        });
+
+        let mut spawned = vec![];
+        let mut failures = vec![];
+        for ((idx, cmd), result) in cmds.iter().zip(results) {
+            match result {
+                Ok(node) => spawned.push((idx, cmd, node)),
+                Err(error) => failures.push(format!("node #{idx}: {error:#}")),
+            }
+        }
+        if !failures.is_empty() {
+            let error = eyre!(
+                "{} of the {} nodes launched together failed to start:\n{}",
+                failures.len(),
+                cmds.len(),
+                failures.join("\n")
+            );
+            // nothing would be left to manage those which did start
+            let mut started: Vec<_> = spawned
+                .into_iter()
+                .map(|(_, _, (node_name, child, _))| (node_name, child))
+                .collect();
+            if !started.is_empty() {
+                warn!(
+                    "Killing the {} nodes launched together which did start",
+                    started.len()
+                );
+                if let Err(kill_error) = process::kill_children(
+                    started
+                        .iter_mut()
+                        .map(|(node_name, child)| (node_name.as_str(), child)),
+                    Duration::from_millis(self.shutdown_timeout_msec),
+                ) {
+                    warn!("{:?}", kill_error);
+                }
+            }
+            return Err(error);
+        }
+
+        spawned
+            .into_iter()
+            .map(|(idx, cmd, (node_name, child, spawn_time))| {
+                phases.record_node_launch(spawn_time);
+                node_span(&node_name, *idx).in_scope(|| {
+                    self.launched_node(cmd, *idx, node_name, child, on_node_address.as_deref_mut())
+                })
+            })
+            .collect()
+    }
+
+    /// The node just spawned from `node_cmd`, with its address discovered if needed.
+    fn launched_node<'a>(
+        &self,
+        node_cmd: &NodeCmd<'a>,
+        node_idx: usize,
+        node_name: String,
+        child: Child,
+        on_node_address: Option<AddressHook<'_, '_>>,
+    ) -> Result<LaunchedNode<'a>> {
+        let addr = self.discover_node_addr(node_idx, &node_name, None, on_node_address)?;
+
+        Ok(LaunchedNode {
+            idx: node_idx,
+            name: node_name,
+            cmd: node_cmd.clone(),
+            child,
+            addr,
+        })
+    }
+
+    /// `node_cmd` with the addresses the node should bind to and advertise: the ones mapped to it
+    /// by --addr-map if any, otherwise just a local address on its --ip or localhost.
+    fn with_node_addrs<'a>(
+        &self,
+        node_cmd: &NodeCmd<'a>,
+        node_idx: usize,
+        addrs: Option<&NodeAddrs>,
+    ) -> NodeCmd<'a> {
+        let mut cmd = node_cmd.clone();
+        match addrs {
+            Some(addrs) => {
+                cmd.push_arg("--local-addr");
+                cmd.push_arg(addrs.local.to_string());
+                if let Some(public_addr) = addrs.public {
+                    cmd.push_arg("--public-addr");
+                    cmd.push_arg(public_addr.to_string());
+                }
+            }
+            None => {
+                if let Some(local_addr) = self.default_local_addr(node_idx) {
+                    cmd.push_arg("--local-addr");
+                    cmd.push_arg(local_addr);
+                }
+            }
+        }
+        cmd
+    }
+
+    /// Local address for the nodes which aren't mapped by --addr-map.
+    fn default_local_addr(&self, node_idx: usize) -> Option<String> {
+        self.local_ip(node_idx)
+            .map(|ip| address::with_port(ip, self.node_port(node_idx)))
+    }
+
+    /// Port the node `node_idx` binds to: its own from --base-port, or `0` for an ephemeral one.
+    fn node_port(&self, node_idx: usize) -> u16 {
+        // checked up front to fit
+        self.base_port
+            .map_or(0, |base_port| base_port + (node_idx - GENESIS_IDX) as u16)
+    }
+
+    /// `result` of launching the node `node_idx`, pointing out the port it was given by
+    /// --base-port if it failed, as the port may be in use.
+    fn with_port_hint<T>(&self, result: Result<T>, node_idx: usize) -> Result<T> {
+        match self.base_port {
+            Some(_) => result.wrap_err_with(|| {
+                format!(
+                    "{} failed to start on port {} given by --base-port, which may already be in use",
+                    self.naming.node_name(node_idx),
+                    self.node_port(node_idx)
+                )
+            }),
+            None => result,
+        }
+    }
+
+    /// IP the node `node_idx` is launched on: its turn of the --ip ones, or a loopback one with
+    /// --local.
+    fn local_ip(&self, node_idx: usize) -> Option<&str> {
+        match self.ip.len() {
+            0 if self.common.is_local => Some(self.common.loopback_ip()),
+            0 => None,
+            len => Some(&self.ip[(node_idx - GENESIS_IDX) % len]),
+        }
+    }
+
+    /// Fail if --addr-map has entries for nodes which aren't being launched.
+    fn check_addr_map(&self, addr_map: &BTreeMap<usize, NodeAddrs>) -> Result<()> {
+        let launching = if self.add_nodes_to_existing_network {
+            self.node_ids()?
+        } else {
+            1..=self.num_nodes
+        };
+
+        let unknown: Vec<_> = addr_map
+            .keys()
+            .filter(|idx| !launching.contains(idx))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(eyre!(
+                "The address map has entries for nodes {:?}, but only nodes {:?} are being launched",
+                unknown,
+                launching
+            ));
+        }
+
+        let unmapped = launching.filter(|idx| !addr_map.contains_key(idx)).count();
+        if unmapped > 0 {
+            info!(
+                "{} node(s) not in the address map will use the default address",
+                unmapped
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Command for the nodes which are reachable, and the ids of the nodes simulated to be behind
+    /// NAT, which are only given a local address.
+    fn nat_setup<'a>(
+        &self,
+        node_cmd: &NodeCmd<'a>,
+        node_ids: &RangeInclusive<usize>,
+    ) -> Result<(NodeCmd<'a>, Range<usize>)> {
+        let behind_nat = match self.behind_nat {
+            Some(count) => count,
+            None => return Ok((node_cmd.clone(), 0..0)),
+        };
+
+        let joining = node_ids.clone().count();
+        if behind_nat > joining {
+            return Err(eyre!(
+                "Cannot put {} nodes behind NAT when only {} nodes are joining",
+                behind_nat,
+                joining
+            ));
+        }
+        let ip = self
+            .local_ip(GENESIS_IDX)
+            .ok_or_else(|| eyre!("--behind-nat requires either --ip or --local"))?;
+
+        let mut reachable_cmd = node_cmd.clone();
+        reachable_cmd.push_arg("--public-addr");
+        reachable_cmd.push_arg(address::with_port(ip, 0));
+
+        Ok((
+            reachable_cmd,
+            node_ids.end() + 1 - behind_nat..node_ids.end() + 1,
+        ))
+    }
+
+    /// Wait for the `node` just restarted by --rolling-restart to be reachable again, on the address
+    /// found in what it logged after `logged_before`, which replaces the one it had.
+    fn wait_for_rejoin(
+        &self,
+        node: &mut LaunchedNode,
+        logged_before: &BTreeMap<PathBuf, u64>,
+    ) -> Result<()> {
+        let node_dir = self.nodes_dir.join(&node.name);
+        node.addr =
+            address::discover_node_addr_since(&node_dir, logged_before, NODE_ADDRESS_TIMEOUT)?;
+        match node.addr {
+            Some(addr) => {
+                debug!("{} is listening on {} once restarted", node.name, addr);
+                readiness::wait_for_connect(
+                    addr,
+                    self.transport == Some(Transport::Tcp),
+                    Duration::from_secs(self.readiness_timeout),
+                    readiness::READINESS_POLL_INTERVAL,
+                )
+            }
+            None => self.warn_or_fail(format!(
+                "Could not discover the address of {} from its logs at {} once restarted, so it can't be told whether it rejoined",
+                node.name,
+                node_dir.display()
+            )),
+        }
+    }
+
+    /// Discover the address of the node, waiting for it to be found in its output by `stdout_addr`
+    /// if it's scanned, and falling back to reading it from its logs.
+    fn discover_node_addr(
+        &self,
+        node_idx: usize,
+        node_name: &str,
+        stdout_addr: Option<&Receiver<SocketAddr>>,
+        on_node_address: Option<AddressHook<'_, '_>>,
+    ) -> Result<Option<SocketAddr>> {
+        if on_node_address.is_none() && !self.needs_node_addrs() {
+            return Ok(None);
+        }
+
+        let node_dir = self.nodes_dir.join(node_name);
+        let addr = match stdout_addr.map(|addr| addr.recv_timeout(NODE_ADDRESS_TIMEOUT)) {
+            Some(Ok(addr)) => {
+                info!(
+                    "Node {} reported listening on {} in its output",
+                    node_name, addr
+                );
+                Some(addr)
+            }
+            Some(Err(_)) => {
+                debug!(
+                    "Node {} didn't report its address in its output, reading its logs",
+                    node_name
+                );
+                // its logs had as long as its output to show the address
+                address::discover_node_addr(&node_dir, Duration::ZERO)?
+            }
+            None => address::discover_node_addr(&node_dir, NODE_ADDRESS_TIMEOUT)?,
+        };
+        match addr {
+            Some(addr) => {
+                debug!("Node {} is listening on {}", node_name, addr);
+                if let Some(on_node_address) = on_node_address {
+                    on_node_address(node_idx, node_name, addr);
+                }
+            }
+            None => self.warn_or_fail(format!(
+                "Could not discover the address of node {} from its logs at {}",
+                node_name,
+                node_dir.display()
+            ))?,
+        }
+
+        if self.connect_probe {
+            if let Some(addr) = addr {
+                self.probe_node(node_name, addr)?;
+            }
+        }
+
+        Ok(addr)
+    }
+
+    /// Wait for the node to be reachable, reporting it if it isn't.
+    fn probe_node(&self, node_name: &str, addr: SocketAddr) -> Result<()> {
+        match readiness::wait_for_connect(
+            addr,
+            self.transport == Some(Transport::Tcp),
+            Duration::from_secs(self.connect_probe_timeout),
+            Duration::from_millis(self.connect_probe_interval_msec),
+        ) {
+            Ok(()) => Ok(()),
+            Err(error) => self.warn_or_fail(format!(
+                "Connect probe of node {node_name} failed: {error:#}"
+            )),
+        }
+    }
+
+    /// Report a condition which doesn't stop the launch, or fail the launch with it with --strict.
+    fn warn_or_fail(&self, message: impl Into<String>) -> Result<()> {
+        let message = message.into();
+        if self.strict {
+            return Err(eyre!("{} (failing the launch due to --strict)", message));
+        }
+        warn!("{}", message);
+        Ok(())
+    }
 
-        if let Some(idle) = self.idle_timeout_msec {
-            node_cmd.push_arg("--idle-timeout-msec");
-            node_cmd.push_arg(idle.to_string());
+    /// The --readiness strategy, waiting for `intervals` with `sleep`.
+    fn readiness_strategy<'s>(
+        &'s self,
+        intervals: &'s mut intervals::Intervals,
+    ) -> Box<dyn ReadinessStrategy + 's> {
+        let timeout = Duration::from_secs(self.readiness_timeout);
+        match self.readiness {
+            Readiness::Sleep => Box::new(readiness::FixedSleep { intervals }),
+            Readiness::File => Box::new(readiness::FileAppears {
+                file: self.readiness_file.clone().unwrap_or_default(),
+                timeout,
+            }),
+            Readiness::LogMarker => Box::new(readiness::LogMarker {
+                marker: self.readiness_log_marker.clone(),
+                timeout,
+            }),
+            Readiness::Connect => Box::new(readiness::Connect {
+                tcp: self.transport == Some(Transport::Tcp),
+                timeout,
+            }),
+            Readiness::Command => Box::new(readiness::ReadyCommand {
+                command: self.readiness_cmd.clone().unwrap_or_default(),
+                nodes_dir: self.nodes_dir.clone(),
+                timeout,
+            }),
         }
+    }
 
-        if let Some(keep_alive_interval_msec) = self.keep_alive_interval_msec {
-            node_cmd.push_arg("--keep-alive-interval-msec");
-            node_cmd.push_arg(keep_alive_interval_msec.to_string());
+    /// Fail if any two nodes of the network, including those launched before when adding nodes,
+    /// were discovered on the same address.
+    fn check_distinct_addrs(&self, launched: &[LaunchedNode]) -> Result<()> {
+        let mut addrs = vec![];
+        if self.add_nodes_to_existing_network {
+            let first_launched = launched.iter().map(|node| node.idx).min();
+            for idx in GENESIS_IDX..first_launched.unwrap_or(GENESIS_IDX) {
+                let name = self.naming.node_name(idx);
+                if let Some(addr) =
+                    address::discover_node_addr(&self.nodes_dir.join(&name), Duration::ZERO)?
+                {
+                    addrs.push((name, addr));
+                }
+            }
         }
+        addrs.extend(
+            launched
+                .iter()
+                .filter_map(|node| Some((node.name.clone(), node.addr?))),
+        );
 
-        if let Some(ip) = &self.ip {
-            node_cmd.push_arg("--local-addr");
-            node_cmd.push_arg(format!("{ip}:0"));
-        } else if self.common.is_local {
-            node_cmd.push_arg("--local-addr");
-            node_cmd.push_arg("127.0.0.1:0");
-        }
+        address::ensure_distinct(addrs.iter().map(|(name, addr)| (name.as_str(), *addr)))?;
+        debug!("All {} discovered node addresses are distinct", addrs.len());
+        Ok(())
+    }
 
-        debug!("Network size: {} nodes", self.num_nodes);
+    /// Whether any of the requested outputs needs the nodes' addresses to be discovered.
+    fn needs_node_addrs(&self) -> bool {
+        self.hosts_file.is_some()
+            || self.addrs_json.is_some()
+            || self.summary_json.is_some()
+            || self.contacts_addrs
+            || self.check_distinct_addrs
+            || self.readiness == Readiness::Connect
+            || self.connect_probe
+            || self.test_genesis_resilience
+            || (self.verify_after.is_some()
+                && self.verify.iter().any(|check| {
+                    matches!(
+                        check,
+                        verify::Check::DistinctAddrs | verify::Check::Reachable
+                    )
+                }))
+    }
 
-        let interval = Duration::from_millis(self.interval);
+    fn node_ids(&self) -> Result<RangeInclusive<usize>> {
+        // only the dirs named as this launch names its nodes, in case others are around, and
+        // numbered after the highest, as some may have been removed
+        let existing = self.naming.existing_node_idxs(&self.nodes_dir)?;
+        if !existing.contains(&GENESIS_IDX) {
+            return Err(eyre!("A genesis node could not be found."));
+        }
+        let last_existing = existing.last().copied().unwrap_or(GENESIS_IDX);
 
-        if !self.add_nodes_to_existing_network {
-            self.run_genesis(&node_cmd)?;
-            thread::sleep(interval);
+        let last_idx: usize = if self.add_nodes_to_existing_network {
+            last_existing + self.nodes_to_add(existing.len())
+        } else {
+            self.num_nodes
+        };
 
-            debug!("Genesis wait over...");
-        }
+        Ok(last_existing + 1..=last_idx)
+    }
 
-        let genesis_contacts_filepath = self.nodes_dir.join("sn-node-genesis").join("section_tree");
+    /// Add a node every minute / `per_minute` from index `first_idx` on, until interrupted or
+    /// --churn-add-cap nodes have been added.
+    #[allow(clippy::too_many_arguments)]
+    fn churn_add<'a>(
+        &'a self,
+        node_cmd: &NodeCmd<'a>,
+        first_idx: usize,
+        per_minute: u32,
+        addr_map: &BTreeMap<usize, NodeAddrs>,
+        node_tags: Option<&tags::NodeTags>,
+        mut memory_guard: Option<&mut memory::MemoryGuard>,
+        mut on_node_address: Option<AddressHook<'_, '_>>,
+        launched: &mut Vec<LaunchedNode<'a>>,
+    ) -> Result<()> {
+        let period = Duration::from_secs(60) / per_minute;
+        let stop = interrupt::stop_on_ctrl_c()?;
+        info!(
+            "Adding a node every {:.1}s from node #{} on, press Ctrl-C to stop",
+            period.as_secs_f64(),
+            first_idx
+        );
 
-        let node_ids = self.node_ids()?;
-        if !node_ids.is_empty() {
-            node_cmd.push_arg("--network-contacts-file");
-            node_cmd.push_arg(&genesis_contacts_filepath);
+        let started = Instant::now();
+        let mut added = 0;
+        for idx in first_idx.. {
+            if self.churn_add_cap.is_some_and(|cap| added >= cap) {
+                info!("Reached the cap of {} added nodes", added);
+                break;
+            }
 
-            debug!(
-                "Common node args for launching the network: {:?}",
-                node_cmd.args()
+            if let Some(memory_guard) = memory_guard.as_deref_mut() {
+                memory_guard.wait_for_free_memory(&self.naming.node_name(idx))?;
+            }
+            let node_started = Instant::now();
+            let cmd = self.with_node_addrs(
+                &with_node_tag(node_cmd, node_tags, idx),
+                idx,
+                addr_map.get(&idx),
+            );
+            launched.push(self.run_node(&cmd, idx, on_node_address.as_deref_mut())?);
+            added += 1;
+            info!(
+                "Added node #{}: {} nodes added in {:.0}s",
+                idx,
+                added,
+                started.elapsed().as_secs_f64()
             );
-            info!("Launching nodes {:?}", node_ids);
 
-            for i in node_ids {
-                self.run_node(&node_cmd, i)?;
-                thread::sleep(interval);
+            if churn::sleep_unless_stopped(period.saturating_sub(node_started.elapsed()), &stop) {
+                info!("Interrupted, stopped adding nodes");
+                break;
             }
         }
 
-        // Let's copy the genesis' section_tree file to the default location for clients to use
-        let client_network_contacts_dir = dirs_next::home_dir()
-            .ok_or_else(|| eyre!("Could not read user's home directory".to_string()))?
-            .join(".safe")
-            .join("network_contacts");
-
         info!(
-            "Copying network contacts file to {} for local clients to bootstrap to the network",
-            client_network_contacts_dir.display()
+            "Added {} nodes in {:.0}s",
+            added,
+            started.elapsed().as_secs_f64()
         );
-        fs::create_dir_all(&client_network_contacts_dir)?;
-        fs::copy(
-            genesis_contacts_filepath,
-            client_network_contacts_dir.join("default"),
-        )?;
-
-        info!("Done!");
         Ok(())
     }
 
-    fn run_genesis(&self, node_cmd: &NodeCmd) -> Result<()> {
-        // Set genesis node's command arguments
-        let mut genesis_cmd = node_cmd.clone();
-        genesis_cmd.push_arg("--first");
-        // `--first` requires a public address. Port `0` means it will be the same as locally bound port.
-        if let Some(ip) = &self.ip {
-            genesis_cmd.push_arg(format!("{ip}:0"));
-        } else if self.common.is_local {
-            genesis_cmd.push_arg("127.0.0.1:0");
+    fn nodes_to_add(&self, existing: usize) -> usize {
+        match self.grow {
+            Some(percentage) => {
+                let delta = (existing * percentage as usize).div_ceil(100);
+                info!(
+                    "Growing the network of {} nodes by {}%: adding {} nodes",
+                    existing, percentage, delta
+                );
+                delta
+            }
+            None => self.num_nodes,
         }
+    }
+}
 
-        // Let's launch genesis node now
-        debug!("Launching genesis node (#1)...");
-        genesis_cmd.run("sn-node-genesis", &self.nodes_dir)?;
+fn parse_num_nodes(value: &str) -> Result<usize, String> {
+    let (value, source) = if value == "-" {
+        let mut line = String::new();
+        std::io::stdin()
+            .read_line(&mut line)
+            .map_err(|error| format!("Could not read the number of nodes from stdin: {error}"))?;
+        (line, " read from stdin")
+    } else {
+        (value.to_string(), "")
+    };
+
+    let num_nodes: usize = value.trim().parse().map_err(|_| {
+        format!(
+            "'{}'{} is not a valid number of nodes",
+            value.trim(),
+            source
+        )
+    })?;
+    if num_nodes == 0 {
+        return Err("The number of nodes must be greater than 0".to_string());
+    }
 
-        Ok(())
+    Ok(num_nodes)
+}
+
+fn parse_percentage(value: &str) -> Result<u32, String> {
+    let percentage: u32 = value
+        .strip_suffix('%')
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{value}' is not a valid percentage, e.g. `20%`"))?;
+
+    if percentage == 0 {
+        return Err("The percentage to grow the network by must be greater than 0".to_string());
     }
 
-    fn run_node(&self, node_cmd: &NodeCmd, node_idx: usize) -> Result<()> {
-        if self.add_nodes_to_existing_network {
-            debug!("Adding node #{}...", node_idx)
-        } else {
-            debug!("Launching node #{}...", node_idx)
-        };
-        node_cmd.run(&format!("sn-node-{node_idx}"), &self.nodes_dir)?;
+    Ok(percentage)
+}
 
-        Ok(())
+impl LaunchedNode<'_> {
+    fn is_genesis(&self) -> bool {
+        self.idx == GENESIS_IDX
     }
+}
 
-    fn node_ids(&self) -> Result<RangeInclusive<usize>> {
-        let paths =
-            fs::read_dir(&self.nodes_dir).wrap_err("Could not read existing testnet log dir")?;
+/// Transport protocol used between nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Transport {
+    Quic,
+    Tcp,
+}
 
-        let count = paths
-            .collect::<Result<Vec<_>, _>>()
-            .wrap_err("Error collecting testnet log dir")?
-            .len();
+impl Transport {
+    fn as_arg(self) -> &'static str {
+        match self {
+            Self::Quic => "quic",
+            Self::Tcp => "tcp",
+        }
+    }
+}
 
-        if count == 0 {
-            return Err(eyre!("A genesis node could not be found."));
+/// Dir clients look for network contacts in, where launching a network copies genesis' contacts.
+fn client_network_contacts_dir() -> Result<PathBuf> {
+    Ok(dirs_next::home_dir()
+        .ok_or_else(|| eyre!("Could not read user's home directory".to_string()))?
+        .join(".safe")
+        .join("network_contacts"))
+}
+
+/// Tracing layer writing the tool's own messages, from debug level on whatever RUST_LOG says, to
+/// `launch.log` in the nodes dir of each launch, for a record of the launch which outlives the
+/// terminal. It's added to the `sn_launch_tool` binary's subscriber.
+pub fn launch_log_layer<S>() -> impl tracing_subscriber::Layer<S>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    launch_log::layer()
+}
+
+/// Span the messages about launching the node `node_idx` are emitted in, so they can be told apart
+/// when nodes are launched together.
+fn node_span(node_name: &str, node_idx: usize) -> Span {
+    info_span!("node", name = %node_name, idx = node_idx)
+}
+
+/// Copy the network contacts file to `destination`, creating its dir if needed.
+fn copy_contacts(contacts: &Path, destination: &Path) -> Result<()> {
+    if let Some(dir) = destination
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+    {
+        fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("Could not create directory {}", dir.display()))?;
+    }
+    fs::copy(contacts, destination).wrap_err_with(|| {
+        format!(
+            "Could not copy {} to {}",
+            contacts.display(),
+            destination.display()
+        )
+    })?;
+    Ok(())
+}
+
+/// Move the network contacts at `destination` aside to `<destination>.bak.<unix time>` before
+/// `contacts` are copied over them, unless there are none or they're the same.
+fn back_up_contacts(contacts: &Path, destination: &Path) -> Result<()> {
+    let previous = match fs::read(destination) {
+        Ok(previous) => previous,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => {
+            return Err(eyre!(error).wrap_err(format!(
+                "Could not read the previous network contacts {}",
+                destination.display()
+            )))
         }
+    };
+    if fs::read(contacts).is_ok_and(|contacts| contacts == previous) {
+        return Ok(());
+    }
 
-        let last_idx: usize = if self.add_nodes_to_existing_network {
-            count + self.num_nodes
-        } else {
-            self.num_nodes
-        };
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    let mut backup = destination.as_os_str().to_os_string();
+    backup.push(format!(".bak.{secs}"));
+    let backup = PathBuf::from(backup);
+    fs::rename(destination, &backup).wrap_err_with(|| {
+        format!(
+            "Could not back up the previous network contacts {} to {}",
+            destination.display(),
+            backup.display()
+        )
+    })?;
+    info!(
+        "Backed up the previous network contacts to {}",
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Remove the network contacts clients use by default, and their --contacts-addrs file, if they're
+/// the `genesis_contacts` of a network being stopped, for `stop --clean-contacts`. Other contacts
+/// are left alone with a warning.
+fn clean_client_contacts(genesis_contacts: &Path) -> Result<()> {
+    let client_contacts = client_network_contacts_dir()?.join(DEFAULT_NETWORK_CONTACTS);
+    let contacts = match fs::read(&client_contacts) {
+        Ok(contacts) => contacts,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            debug!("There are no client network contacts to clean");
+            return Ok(());
+        }
+        Err(error) => {
+            return Err(eyre!(error).wrap_err(format!(
+                "Could not read the client network contacts {}",
+                client_contacts.display()
+            )))
+        }
+    };
+    if !fs::read(genesis_contacts).is_ok_and(|genesis_contacts| genesis_contacts == contacts) {
+        warn!(
+            "Leaving the client network contacts {} alone, as they aren't the ones in {}",
+            client_contacts.display(),
+            genesis_contacts.display()
+        );
+        return Ok(());
+    }
 
-        Ok(count + 1..=last_idx)
+    fs::remove_file(&client_contacts).wrap_err_with(|| {
+        format!(
+            "Could not remove the client network contacts {}",
+            client_contacts.display()
+        )
+    })?;
+    let mut addrs_path = client_contacts.clone().into_os_string();
+    addrs_path.push(CONTACTS_ADDRS_SUFFIX);
+    match fs::remove_file(&addrs_path) {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => warn!(
+            "Could not remove {}: {}",
+            Path::new(&addrs_path).display(),
+            error
+        ),
+        _ => {}
     }
+    info!(
+        "Removed the client network contacts {}",
+        client_contacts.display()
+    );
+    Ok(())
 }
 
 /// Run a Safe node to join a network
@@ -227,9 +2905,15 @@ pub struct Join {
     #[clap(long = "clear-data", value_parser)]
     clear_data: bool,
 
-    /// Network contacts list file path
+    /// Network contacts list file path (default: the one launching a network copies for local
+    /// clients, `~/.safe/network_contacts/default`, to join the network launched last)
     #[clap(long, value_parser)]
-    network_contacts_file: PathBuf,
+    network_contacts_file: Option<PathBuf>,
+
+    /// Run the node as a passive observer which only bootstraps to the network and watches its
+    /// events, without becoming a full member. Requires sn_node support for `--observer`.
+    #[clap(long, value_parser)]
+    observer: bool,
 }
 
 impl Join {
@@ -242,12 +2926,21 @@ impl Join {
             node_cmd.push_arg(max_capacity.to_string());
         }
 
+        if let Some(addr) = self
+            .local_addr
+            .filter(|addr| self.common.ipv6 && addr.is_ipv4())
+        {
+            return Err(eyre!(
+                "--ipv6 can't be combined with the IPv4 --local-addr {}",
+                addr
+            ));
+        }
         if let Some(local_addr) = self.local_addr {
             node_cmd.push_arg("--local-addr");
             node_cmd.push_arg(local_addr.to_string());
         } else if self.common.is_local {
             node_cmd.push_arg("--local-addr");
-            node_cmd.push_arg("127.0.0.1:0");
+            node_cmd.push_arg(address::with_port(self.common.loopback_ip(), 0));
         }
 
         if let Some(public_addr) = self.public_addr {
@@ -259,18 +2952,48 @@ impl Join {
             node_cmd.push_arg("--clear-data");
         }
 
+        if self.observer {
+            node_cmd.ensure_supports("--observer")?;
+            node_cmd.push_arg("--observer");
+        }
+
+        let network_contacts_file = match &self.network_contacts_file {
+            Some(network_contacts_file) => network_contacts_file.clone(),
+            None => {
+                let default = client_network_contacts_dir()?.join(DEFAULT_NETWORK_CONTACTS);
+                if !default.is_file() {
+                    return Err(eyre!(
+                        "No network contacts at {}, launch a network first or pass --network-contacts-file",
+                        default.display()
+                    ));
+                }
+                info!("Joining the network with contacts {}", default.display());
+                default
+            }
+        };
         node_cmd.push_arg("--network-contacts-file");
-        node_cmd.push_arg(self.network_contacts_file.clone());
+        node_cmd.push_arg(network_contacts_file);
 
-        debug!("Launching node...");
-        node_cmd.run(
-            "", // no name passed
-            &self.nodes_dir,
-        )?;
+        if self.common.check_node_flags {
+            node_cmd.check_flags(&[])?;
+        }
+
+        if self.common.dry_run {
+            node_cmd.print_invocation(
+                &naming::next_joined_node_name(&self.nodes_dir),
+                &self.nodes_dir,
+            );
+            return Ok(());
+        }
+
+        // a dir of its own, so it doesn't collide with launched nodes or other joined ones
+        let node_name = naming::claim_joined_node_dir(&self.nodes_dir)?;
+        debug!("Launching node {}...", node_name);
+        node_cmd.run(&node_name, &self.nodes_dir)?;
 
         debug!(
             "Node logs are being stored at: {}/sn_node.log<DATETIME>",
-            self.nodes_dir.display()
+            self.nodes_dir.join(&node_name).display()
         );
         debug!("(Note that log files are rotated hourly, and subsequent files will be named sn_node.log<NEW DATE TINE>.");
 
@@ -278,12 +3001,308 @@ impl Join {
     }
 }
 
+/// Pause a launched node by freezing its process (SIGSTOP), without killing it
+#[derive(Debug, clap::StructOpt)]
+pub struct Pause {
+    #[clap(flatten)]
+    node: LaunchedNodeArgs,
+}
+
+impl Pause {
+    /// Pause the node with these arguments.
+    pub fn run(&self) -> Result<()> {
+        let (name, pid) = self.node.pid()?;
+        process::pause(pid).wrap_err_with(|| format!("Could not pause {name}"))?;
+        info!("Paused {} (PID {})", name, pid);
+
+        Ok(())
+    }
+}
+
+/// Resume a node paused with `pause` (SIGCONT)
+#[derive(Debug, clap::StructOpt)]
+pub struct Resume {
+    #[clap(flatten)]
+    node: LaunchedNodeArgs,
+}
+
+impl Resume {
+    /// Resume the node with these arguments.
+    pub fn run(&self) -> Result<()> {
+        let (name, pid) = self.node.pid()?;
+        process::resume(pid).wrap_err_with(|| format!("Could not resume {name}"))?;
+        info!("Resumed {} (PID {})", name, pid);
+
+        Ok(())
+    }
+}
+
+/// Stop the nodes of a launched network, using the PIDs recorded in its nodes dir
+#[derive(Debug, clap::StructOpt)]
+pub struct Stop {
+    /// Path where the output directories for all the nodes were written
+    #[clap(short = 'd', long, default_value = "./nodes", value_parser)]
+    nodes_dir: PathBuf,
+
+    /// Order to stop the nodes in, one at a time: `reverse` launch order, which stops genesis last,
+    /// `launch` order, `genesis-first` followed by the other nodes in reverse launch order, e.g.
+    /// to test abrupt genesis loss, or `genesis-last` after the other nodes in launch order.
+    #[clap(long, value_enum, default_value = "reverse")]
+    stop_order: StopOrder,
+
+    /// Kill the nodes which are still running --grace-secs after being asked to terminate
+    /// (SIGKILL on Unix).
+    #[clap(long, value_parser)]
+    force: bool,
+
+    /// Seconds to wait for each node to exit after being asked to terminate, before moving on to
+    /// the next one, or killing it with --force.
+    #[clap(long, default_value = "10", value_parser)]
+    grace_secs: u64,
+
+    /// Once the nodes are stopped, remove the network contacts clients use by default
+    /// (`~/.safe/network_contacts/default`) if they're the ones genesis of this network wrote, so
+    /// clients don't try to bootstrap to a network which is gone. Contacts of another network are
+    /// left alone.
+    #[clap(long, value_parser)]
+    clean_contacts: bool,
+}
+
+impl Stop {
+    /// Stop the network with these arguments.
+    pub fn run(&self) -> Result<()> {
+        let pids = process::read_network_pids(&self.nodes_dir)?;
+        let count = pids.len();
+        // nodes are listed in launch order, so genesis comes first unless there's none
+        let has_genesis = pids
+            .first()
+            .is_some_and(|(name, _)| !naming::is_joined_node_name(name));
+        let grace = Duration::from_secs(self.grace_secs);
+        let genesis_contacts =
+            has_genesis.then(|| self.nodes_dir.join(&pids[0].0).join(GENESIS_CONTACTS_FILE));
+
+        let mut stopped = 0;
+        for (name, pid) in self.stop_order.arrange(pids, has_genesis) {
+            if process::is_alive(pid) == Some(false) {
+                debug!("{} (PID {}) isn't running", name, pid);
+                continue;
+            }
+            if let Err(error) = process::terminate(pid) {
+                debug!("Could not stop {}: {:?}", name, error);
+                continue;
+            }
+            debug!("Stopping {} (PID {})", name, pid);
+            stopped += 1;
+
+            if process::wait_for_exits(&[pid], grace).is_empty() {
+                continue;
+            }
+            if self.force {
+                warn!(
+                    "{} (PID {}) didn't exit within {}s, killing it",
+                    name, pid, self.grace_secs
+                );
+                if let Err(error) = process::kill(pid) {
+                    debug!("Could not kill {}: {:?}", name, error);
+                }
+            } else {
+                warn!(
+                    "{} (PID {}) is still running {}s after being asked to terminate, moving on",
+                    name, pid, self.grace_secs
+                );
+            }
+        }
+
+        info!(
+            "Stopped {} nodes in {}, {} weren't running",
+            stopped,
+            self.nodes_dir.display(),
+            count - stopped
+        );
+
+        if let Err(error) = netem::remove_loopback_delay(&self.nodes_dir) {
+            warn!("{:?}", error);
+        }
+
+        if self.clean_contacts {
+            match genesis_contacts {
+                Some(genesis_contacts) => clean_client_contacts(&genesis_contacts)?,
+                None => warn!(
+                    "Not cleaning the client network contacts, as there's no genesis in {}",
+                    self.nodes_dir.display()
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Follow the logs of all the nodes of a launched network, merged into one view
+#[derive(Debug, clap::StructOpt)]
+pub struct TailAll {
+    /// Path where the output directories for all the nodes were written
+    #[clap(short = 'd', long, default_value = "./nodes", value_parser)]
+    nodes_dir: PathBuf,
+
+    #[clap(flatten)]
+    naming: NamingArgs,
+
+    /// Only follow the logs of the node with this index, the genesis node being #1. Can be given
+    /// more than once.
+    #[clap(long = "node", value_parser)]
+    nodes: Vec<usize>,
+
+    /// Only show the log lines of at least this level. Lines without a level, e.g. the
+    /// continuations of multi-line messages, are always shown.
+    #[clap(long, value_enum)]
+    level: Option<tail::LogLevel>,
+}
+
+impl TailAll {
+    /// Follow the logs with these arguments, until interrupted.
+    pub fn run(&self) -> Result<()> {
+        let nodes = if self.nodes.is_empty() {
+            let mut nodes: Vec<_> = fs::read_dir(&self.nodes_dir)
+                .wrap_err_with(|| format!("Could not read {}", self.nodes_dir.display()))?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.join(process::PID_FILE).is_file() || logs::newest_log_file(path).is_some()
+                })
+                .filter_map(|path| {
+                    let name = path.file_name()?.to_string_lossy().into_owned();
+                    Some((name, path))
+                })
+                .collect();
+            nodes.sort();
+            nodes
+        } else {
+            self.nodes
+                .iter()
+                .map(|&idx| {
+                    let name = self.naming.node_name(idx);
+                    let node_dir = self.nodes_dir.join(&name);
+                    if node_dir.is_dir() {
+                        Ok((name, node_dir))
+                    } else {
+                        Err(eyre!("Node #{} not found at {}", idx, node_dir.display()))
+                    }
+                })
+                .collect::<Result<_>>()?
+        };
+        if nodes.is_empty() {
+            return Err(eyre!("No nodes found in {}", self.nodes_dir.display()));
+        }
+
+        info!(
+            "Following the logs of {} nodes, press Ctrl-C to stop",
+            nodes.len()
+        );
+        tail::follow_all(nodes, self.level);
+
+        Ok(())
+    }
+}
+
+/// List the networks launched under a base dir, with the sn_node version they were launched with,
+/// how many of their nodes are still running, and which were restarted or exited
+#[derive(Debug, clap::StructOpt)]
+pub struct List {
+    /// Dir to search for networks, i.e. nodes dirs, in
+    #[clap(default_value = ".", value_parser)]
+    base_dir: PathBuf,
+}
+
+impl List {
+    /// List the networks with these arguments.
+    pub fn run(&self) -> Result<()> {
+        let networks = list::find_networks(&self.base_dir)?;
+        if networks.is_empty() {
+            info!("No networks found under {}", self.base_dir.display());
+            return Ok(());
+        }
+
+        for network in networks {
+            let alive = match network.alive {
+                Some(alive) => format!("{alive}/{} running", network.nodes),
+                None => "running unknown".to_string(),
+            };
+            let launched = match network.launched_at {
+                Some(launched_at) => format!("launched {}", list::ago(launched_at)),
+                None => "launch time unknown".to_string(),
+            };
+            let version = if network.versions.is_empty() {
+                "version unknown".to_string()
+            } else {
+                network.versions.join(", ")
+            };
+            println!(
+                "{}: {} nodes, {}, {}, {}",
+                network.nodes_dir.display(),
+                network.nodes,
+                launched,
+                version,
+                alive
+            );
+            for (name, restarts) in network.restarts {
+                let last_exit = restarts.last_exit.as_deref().unwrap_or("none");
+                println!(
+                    "  {}: restarted {} times, last exit: {}",
+                    name, restarts.count, last_exit
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, clap::StructOpt)]
+struct LaunchedNodeArgs {
+    /// Index of the node, as in its `sn-node-<index>` dir name. The genesis node is #1.
+    #[clap(value_parser)]
+    node_idx: usize,
+
+    #[clap(flatten)]
+    naming: NamingArgs,
+
+    /// Path where the output directories for all the nodes were written
+    #[clap(short = 'd', long, default_value = "./nodes", value_parser)]
+    nodes_dir: PathBuf,
+}
+
+impl LaunchedNodeArgs {
+    /// Name of the node and the PID recorded when it was launched.
+    fn pid(&self) -> Result<(String, u32)> {
+        if self.node_idx < GENESIS_IDX {
+            return Err(eyre!("Node indices start at {} (genesis)", GENESIS_IDX));
+        }
+        let name = self.naming.node_name(self.node_idx);
+        let pid = process::read_pid(&self.nodes_dir.join(&name))?;
+
+        Ok((name, pid))
+    }
+}
+
+#[derive(Clone, Debug, clap::StructOpt)]
+#[clap(group = clap::ArgGroup::new("cargo").args(&["cargo-run", "build-first"]))]
 struct CommonArgs {
     /// Path where to locate sn_node/sn_node.exe binary. The SN_NODE_PATH env var can be also used to set the path
     #[clap(short = 'p', long, env = "SN_NODE_PATH", value_parser)]
     node_path: Option<PathBuf>,
 
+    /// File name of the sn_node binary in `~/.safe/node`, e.g. for a renamed build or a wrapper,
+    /// rather than sn_node (sn_node.exe on Windows). Ignored when --node-path or SN_NODE_PATH is
+    /// given, which name the whole path.
+    #[clap(long, value_parser)]
+    node_bin_name: Option<String>,
+
+    /// Launch sn_node binaries older than the oldest supported version, or whose version can't be
+    /// told, e.g. custom builds
+    #[clap(long, value_parser)]
+    skip_version_check: bool,
+
     /// Verbosity level for nodes logs (default: INFO)
     #[clap(short = 'y', long, action = clap::ArgAction::Count)]
     nodes_verbosity: u8,
@@ -296,28 +3315,174 @@ struct CommonArgs {
     #[clap(long, value_parser)]
     json_logs: bool,
 
+    /// Log rotation policy forwarded to the nodes as `--log-rotation`, e.g. `daily`, if the
+    /// sn_node binary supports it (it rotates its logs hourly by default).
+    #[clap(long, value_parser)]
+    log_rotation: Option<String>,
+
+    /// Maximum number of rotated log files each node keeps, forwarded to the nodes as
+    /// `--log-max-files`, if the sn_node binary supports it.
+    #[clap(long, value_parser)]
+    log_max_files: Option<u32>,
+
+    /// Bootstrap cache dir forwarded to the nodes as `--bootstrap-cache`, e.g. to reuse the peers
+    /// cached by a previous run. Requires an sn_node binary which supports it.
+    #[clap(long, value_parser)]
+    bootstrap_cache: Option<PathBuf>,
+
+    /// Start each node with an empty bootstrap cache of its own, in its node dir, rather than
+    /// one left over by a previous run. Requires an sn_node binary which supports
+    /// `--bootstrap-cache`.
+    #[clap(long, conflicts_with = "bootstrap-cache", value_parser)]
+    no_bootstrap_cache: bool,
+
+    /// Run the nodes fully in the background: detached from the terminal (in a new session on
+    /// Unix, without a console on Windows) so they survive it being closed, with their stdout and
+    /// stderr written to `stdout.log` and `stderr.log` in their node dirs.
+    #[clap(long, value_parser)]
+    detach: bool,
+
+    /// Write each node's stdout and stderr to `stdout.log` and `stderr.log` in its node dir rather
+    /// than to the terminal, where the output of many nodes interleaves. The files of a node's
+    /// previous runs are kept as `stdout.log.1` (the latest) to `stdout.log.3`. The nodes'
+    /// addresses are still discovered from their logs.
+    #[clap(long, value_parser)]
+    capture_output: bool,
+
+    /// Write the env vars each node is run with, including its resolved RUST_LOG, to `env.txt` in
+    /// its node dir, one `KEY=value` per line, to tell what it actually ran with.
+    #[clap(long, value_parser)]
+    record_env: bool,
+
+    /// Fail a node's launch, killing it, if a line of its log matches this regex once it has
+    /// survived the liveness check, e.g. `panicked|ERROR`, reporting the matching line. Only the
+    /// first MiB of the log is scanned. Can be given more than once.
+    #[clap(long, value_parser)]
+    fail_on_log_pattern: Vec<regex::Regex>,
+
+    /// Sanitizer the sn_node binary was built with, e.g. `-Zsanitizer=address`. Each node is run
+    /// with ASAN_OPTIONS or TSAN_OPTIONS writing its reports to `asan.<pid>` or `tsan.<pid>` in its
+    /// node dir, and nodes leaving reports behind are listed once launched.
+    #[clap(long, value_enum)]
+    sanitizer: Option<Sanitizer>,
+
+    /// Options for the --sanitizer runtime, replacing the recommended ones (`halt_on_error=0`,
+    /// plus `detect_leaks=1:detect_stack_use_after_return=1` for address, or
+    /// `second_deadlock_stack=1:history_size=7` for thread). `log_path` is always set to the node's
+    /// dir, unless given here.
+    #[clap(long, requires = "sanitizer", value_parser)]
+    sanitizer_options: Option<String>,
+
     /// Run the section locally.
     #[clap(long = "local", value_parser)]
     is_local: bool,
 
+    /// With --local, run the nodes on the IPv6 loopback address `::1` rather than on `127.0.0.1`,
+    /// each on a port of its own, for dual-stack testing. Can't be combined with an IPv4 --ip or
+    /// --local-addr.
+    #[clap(long, requires = "is-local", value_parser)]
+    ipv6: bool,
+
     /// Run the nodes using `cargo flamegraph` (which needs to be preinstalled.)
     /// It is recommended to manually run `cargo flamegraph --root --bin=sn_node -- --first` to ensure
     /// everything is built. (This command will fail dur to insufficient args, but that's okay, carry
     /// testnetting w/ --flame thereafter)
     #[clap(long = "flame", value_parser)]
     flame: bool,
+
+    /// With --flame, launch the nodes normally, with a warning, if `cargo flamegraph` isn't
+    /// installed, rather than failing the launch.
+    #[clap(long, requires = "flame", value_parser)]
+    flame_fallback: bool,
+
+    /// Run the nodes with `cargo run` from a sn_node workspace instead of a prebuilt binary, so
+    /// each launch uses the current source. sn_node is built once before launching any node.
+    #[clap(long, conflicts_with = "flame", value_parser)]
+    cargo_run: bool,
+
+    /// Build sn_node with `cargo build` from a sn_node workspace once before launching, and run
+    /// all the nodes from the freshly built binary rather than from --node-path. The launch is
+    /// aborted if the build fails.
+    #[clap(long, conflicts_with = "flame", value_parser)]
+    build_first: bool,
+
+    /// Path to the Cargo.toml of the sn_node workspace used by --cargo-run or --build-first
+    /// (default: the one in the current directory).
+    #[clap(long, requires = "cargo", value_parser)]
+    manifest_path: Option<PathBuf>,
+
+    /// Cargo profile to build sn_node with for --cargo-run or --build-first, e.g. `release`.
+    #[clap(long, requires = "cargo", value_parser)]
+    cargo_profile: Option<String>,
+
+    /// Maximum number of bytes of a node's most recent log output to report when it exits early.
+    /// Only the tail of the log is read, so this bounds the memory used regardless of log size.
+    #[clap(long, default_value_t = cmd::DEFAULT_EXIT_LOG_BYTES, value_parser)]
+    exit_log_bytes: u64,
+
+    /// Milliseconds to wait after spawning each node before checking it hasn't exited early,
+    /// failing the launch if it has. 0 disables the early exit check entirely.
+    #[clap(long, default_value_t = cmd::DEFAULT_LIVENESS_TIMEOUT_MSEC, value_parser)]
+    liveness_timeout_msec: u64,
+
+    /// Number of times to respawn a node which exits with a failure within the liveness timeout,
+    /// e.g. having lost a race for its port, before failing the launch. Each retry is logged.
+    #[clap(long, default_value_t = cmd::DEFAULT_SPAWN_RETRIES, value_parser)]
+    spawn_retries: u32,
+
+    /// Check that the sn_node binary supports every flag the tool will launch it with, according
+    /// to its `--help` output, before launching any node.
+    #[clap(long, value_parser)]
+    check_node_flags: bool,
+
+    /// Print the command each node would be launched with, one line per node with its args and
+    /// env, instead of launching any node.
+    #[clap(long, value_parser)]
+    dry_run: bool,
+
+    /// Args passed as they are to every node after `--`, e.g. `-- --some-node-flag value`, for the
+    /// sn_node flags the tool doesn't have options for. They come after the args the tool manages,
+    /// except the node's --root-dir and --log-dir.
+    #[clap(last = true, value_parser)]
+    node_args: Vec<OsString>,
 }
 
 impl CommonArgs {
-    fn node_cmd(&self) -> Result<NodeCmd> {
-        let mut cmd = match self.node_path.as_deref() {
-            Some(p) => NodeCmd::new(p),
-            None => {
+    fn node_cmd(&self) -> Result<NodeCmd<'_>> {
+        let built_path = if self.build_first {
+            Some(cmd::build_node_binary(
+                self.manifest_path.as_deref(),
+                self.cargo_profile.as_deref(),
+            )?)
+        } else {
+            None
+        };
+
+        let mut cmd = match (built_path, self.node_path.as_deref()) {
+            (Some(built_path), _) => NodeCmd::new(built_path),
+            (None, Some(p)) => {
+                if let Some(bin_name) = &self.node_bin_name {
+                    warn!(
+                        "Ignoring --node-bin-name {} as the sn_node path is given by --node-path or SN_NODE_PATH",
+                        bin_name
+                    );
+                }
+                NodeCmd::new(p)
+            }
+            (None, None) => {
+                let bin_name = self.node_bin_name.as_deref().unwrap_or(SN_NODE_EXECUTABLE);
+                if Path::new(bin_name).file_name() != Some(OsStr::new(bin_name)) {
+                    return Err(eyre!(
+                        "--node-bin-name {} isn't a file name, pass --node-path for a path",
+                        bin_name
+                    ));
+                }
+
                 let mut path =
                     dirs_next::home_dir().ok_or_else(|| eyre!("Home directory not found"))?;
 
                 path.push(".safe/node");
-                path.push(SN_NODE_EXECUTABLE);
+                path.push(bin_name);
                 NodeCmd::new(path)
             }
         };
@@ -337,18 +3502,82 @@ impl CommonArgs {
         }
 
         if self.flame {
-            cmd.set_flame(self.flame);
+            // checked once up front, as every node would fail to spawn otherwise
+            if cmd::flamegraph_installed() {
+                cmd.set_flame(self.flame);
+            } else if self.flame_fallback {
+                warn!("`cargo flamegraph` isn't installed, launching the nodes without flame graphs (install it with `cargo install flamegraph`)");
+            } else {
+                return Err(eyre!(
+                    "--flame requires `cargo flamegraph`, which isn't installed: install it with `cargo install flamegraph`, or pass --flame-fallback to launch without flame graphs"
+                ));
+            }
         }
 
-        debug!(
-            "Using sn_node @ {} from {}",
-            cmd.version()?,
-            cmd.path().display()
-        );
+        cmd.set_exit_log_bytes(self.exit_log_bytes);
+        cmd.set_liveness_timeout(Duration::from_millis(self.liveness_timeout_msec));
+        cmd.set_spawn_retries(self.spawn_retries);
+        cmd.set_detach(self.detach);
+        cmd.set_output_to_files(self.capture_output);
+        cmd.set_record_env(self.record_env);
+        cmd.set_passthrough_args(&self.node_args);
+        cmd.set_fail_log_patterns(self.fail_on_log_pattern.clone());
+        if let Some(sanitizer) = self.sanitizer {
+            let options = self
+                .sanitizer_options
+                .as_deref()
+                .unwrap_or_else(|| sanitizer.default_options());
+            cmd.set_sanitizer(sanitizer, options);
+        }
+
+        if self.cargo_run {
+            cmd.set_cargo_run(self.manifest_path.as_deref(), self.cargo_profile.as_deref());
+            cmd.build()?;
+        }
+        let version = cmd.version()?;
+        if self.cargo_run {
+            debug!("Using sn_node @ {} from `cargo run`", version);
+        } else {
+            debug!("Using sn_node @ {} from {}", version, cmd.path().display());
+        }
+        if !self.skip_version_check {
+            probe::ensure_supported_version(&version)?;
+        }
+
+        // only checked once the binary is known, as it may be run with `cargo run`
+        if let Some(log_rotation) = &self.log_rotation {
+            cmd.ensure_supports("--log-rotation")?;
+            cmd.push_arg("--log-rotation");
+            cmd.push_arg(log_rotation.as_str());
+        }
+
+        if let Some(log_max_files) = self.log_max_files {
+            cmd.ensure_supports("--log-max-files")?;
+            cmd.push_arg("--log-max-files");
+            cmd.push_arg(log_max_files.to_string());
+        }
+
+        if let Some(bootstrap_cache) = &self.bootstrap_cache {
+            cmd.ensure_supports("--bootstrap-cache")?;
+            cmd.push_arg("--bootstrap-cache");
+            cmd.push_arg(bootstrap_cache);
+        } else if self.no_bootstrap_cache {
+            cmd.ensure_supports("--bootstrap-cache")?;
+            cmd.set_fresh_bootstrap_cache(true);
+        }
 
         Ok(cmd)
     }
 
+    /// Loopback IP the nodes are run on with --local.
+    fn loopback_ip(&self) -> &'static str {
+        if self.ipv6 {
+            "::1"
+        } else {
+            "127.0.0.1"
+        }
+    }
+
     fn rust_log(&self) -> Cow<'_, str> {
         match self.rust_log.as_deref() {
             Some(rust_log_flag) => rust_log_flag.into(),
@@ -359,3 +3588,78 @@ impl CommonArgs {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn added_nodes_are_numbered_after_the_highest_existing_node() -> Result<()> {
+        let nodes_dir =
+            std::env::temp_dir().join(format!("sn_launch_tool-node-ids-{}", std::process::id()));
+        fs::create_dir_all(nodes_dir.join("sn-node-genesis"))?;
+        fs::create_dir_all(nodes_dir.join("sn-node-3"))?;
+        fs::write(nodes_dir.join(GENESIS_CONTACTS_FILE), "stray")?;
+
+        let launch = Launch::try_parse_from([
+            "sn_launch_tool".as_ref(),
+            "--add".as_ref(),
+            "--num-nodes".as_ref(),
+            "2".as_ref(),
+            "--nodes-dir".as_ref(),
+            nodes_dir.as_os_str(),
+        ])?;
+        let node_ids = launch.node_ids();
+        fs::remove_dir_all(&nodes_dir)?;
+
+        assert_eq!(node_ids?, 4..=5);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn nodes_can_be_added_more_than_once_with_kill_on_interrupt() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let nodes_dir =
+            std::env::temp_dir().join(format!("sn_launch_tool-add-nodes-{}", std::process::id()));
+        let genesis_dir = nodes_dir.join("sn-node-genesis");
+        fs::create_dir_all(&genesis_dir)?;
+        fs::write(genesis_dir.join(GENESIS_CONTACTS_FILE), "tree")?;
+        let node_path = nodes_dir.join("sn_node");
+        fs::write(
+            &node_path,
+            // like sn_node, it creates its dir, which the next nodes added are numbered after
+            "#!/bin/sh\n\
+             [ \"$1\" = -V ] && echo 'sn_node 0.80.0' && exit 0\n\
+             while [ \"$1\" != --root-dir ]; do shift; done\n\
+             mkdir -p \"$2\" && exec sleep 30\n",
+        )?;
+        fs::set_permissions(&node_path, fs::Permissions::from_mode(0o755))?;
+
+        let launch = Launch::try_parse_from([
+            "sn_launch_tool".as_ref(),
+            "--kill-on-interrupt".as_ref(),
+            "--no-default-contacts".as_ref(),
+            "--interval".as_ref(),
+            "0".as_ref(),
+            "--liveness-timeout-msec".as_ref(),
+            "200".as_ref(),
+            "--node-path".as_ref(),
+            node_path.as_os_str(),
+            "--nodes-dir".as_ref(),
+            nodes_dir.as_os_str(),
+        ])?;
+        let mut nodes = launch.add_nodes(1)?;
+        let added_again = launch.add_nodes(1).map(|added| nodes.extend(added));
+        let shut_down = launch.shut_down(&mut nodes);
+        fs::remove_dir_all(&nodes_dir)?;
+
+        added_again?;
+        shut_down?;
+        let names: Vec<_> = nodes.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, ["sn-node-2", "sn-node-3"]);
+        Ok(())
+    }
+}
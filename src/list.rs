@@ -0,0 +1,136 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::process::{self, Restarts};
+use eyre::{Result, WrapErr};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// File in the nodes dir listing the versions of sn_node its nodes were launched with, one per line.
+pub(crate) const VERSIONS_FILE: &str = "sn_node.versions";
+
+/// A network found under a base dir, from the PID files of its nodes.
+pub(crate) struct NetworkSummary {
+    pub(crate) nodes_dir: PathBuf,
+    pub(crate) nodes: usize,
+    /// Number of the nodes still running, if that can be told on this platform.
+    pub(crate) alive: Option<usize>,
+    /// When the first of its nodes was launched.
+    pub(crate) launched_at: Option<SystemTime>,
+    /// Versions of sn_node its nodes were launched with, if they were recorded.
+    pub(crate) versions: Vec<String>,
+    /// The name and record of each of its nodes which was restarted or exited, sorted by name.
+    pub(crate) restarts: Vec<(String, Restarts)>,
+}
+
+/// Find the networks under `base_dir`: the dirs, including `base_dir` itself, with node dirs
+/// which recorded a PID.
+pub(crate) fn find_networks(base_dir: &Path) -> Result<Vec<NetworkSummary>> {
+    let mut networks = vec![];
+    let mut dirs = vec![base_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = fs::read_dir(&dir)
+            .wrap_err_with(|| format!("Could not read directory {}", dir.display()))?;
+        let mut subdirs = vec![];
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                subdirs.push(entry.path());
+            }
+        }
+
+        let pids: Vec<_> = subdirs
+            .iter()
+            .filter_map(|node_dir| {
+                let pid = process::read_pid(node_dir).ok()?;
+                let written = fs::metadata(node_dir.join(process::PID_FILE))
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                Some((pid, written))
+            })
+            .collect();
+        if pids.is_empty() {
+            // node dirs aren't searched, as they hold no networks of their own
+            subdirs.sort();
+            dirs.extend(subdirs.into_iter().rev());
+            continue;
+        }
+
+        let alive = pids
+            .iter()
+            .map(|(pid, _)| process::is_alive(*pid).map(usize::from))
+            .sum::<Option<usize>>();
+        let mut restarts: Vec<_> = subdirs
+            .iter()
+            .filter_map(|node_dir| {
+                let record = Restarts::load(node_dir);
+                if record == Restarts::default() {
+                    return None;
+                }
+                Some((node_dir.file_name()?.to_string_lossy().into_owned(), record))
+            })
+            .collect();
+        restarts.sort_by(|(name, _), (other, _)| name.cmp(other));
+        networks.push(NetworkSummary {
+            versions: read_versions(&dir),
+            nodes_dir: dir,
+            nodes: pids.len(),
+            alive,
+            launched_at: pids.iter().filter_map(|(_, written)| *written).min(),
+            restarts,
+        });
+    }
+
+    Ok(networks)
+}
+
+/// Record that nodes in `nodes_dir` run sn_node `version`, alongside the versions recorded there
+/// already unless `replace`, e.g. as all its nodes were restarted with it.
+pub(crate) fn record_version(nodes_dir: &Path, version: &str, replace: bool) -> Result<()> {
+    let mut versions = if replace {
+        vec![]
+    } else {
+        read_versions(nodes_dir)
+    };
+    if !versions.iter().any(|recorded| recorded == version) {
+        versions.push(version.to_string());
+    }
+
+    let path = nodes_dir.join(VERSIONS_FILE);
+    fs::create_dir_all(nodes_dir)
+        .and_then(|()| fs::write(&path, versions.join("\n") + "\n"))
+        .wrap_err_with(|| format!("Could not write sn_node versions {}", path.display()))
+}
+
+/// The versions of sn_node recorded in `nodes_dir`, if any.
+fn read_versions(nodes_dir: &Path) -> Vec<String> {
+    fs::read_to_string(nodes_dir.join(VERSIONS_FILE))
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|version| !version.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// How long ago `time` was, e.g. `3h ago`.
+pub(crate) fn ago(time: SystemTime) -> String {
+    let elapsed = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    match elapsed {
+        0..=59 => format!("{elapsed}s ago"),
+        60..=3599 => format!("{}m ago", elapsed / 60),
+        3600..=86399 => format!("{}h ago", elapsed / 3600),
+        _ => format!("{}d ago", elapsed / 86400),
+    }
+}
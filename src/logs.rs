@@ -0,0 +1,77 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Prefix of the (hourly rotated) log files sn_node writes into its log dir.
+pub(crate) const NODE_LOG_PREFIX: &str = "sn_node.log";
+
+/// The most recently modified log file in the node's log dir, if any.
+pub(crate) fn newest_log_file(log_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(log_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(NODE_LOG_PREFIX)
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .max()
+        .map(|(_, path)| path)
+}
+
+/// Up to the last `max_bytes` of the node's newest log file, without reading the whole file.
+///
+/// The first line is dropped when the tail starts mid-file, since it's most likely partial.
+pub(crate) fn tail(log_dir: &Path, max_bytes: u64) -> String {
+    let mut file = match newest_log_file(log_dir).and_then(|path| File::open(path).ok()) {
+        Some(file) => file,
+        None => return String::new(),
+    };
+
+    let len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    let start = len.saturating_sub(max_bytes);
+    let mut bytes = vec![];
+    if file
+        .seek(SeekFrom::Start(start))
+        .and_then(|_| file.take(max_bytes).read_to_end(&mut bytes))
+        .is_err()
+    {
+        return String::new();
+    }
+
+    let tail = String::from_utf8_lossy(&bytes);
+    match tail.split_once('\n') {
+        Some((_, rest)) if start > 0 => rest.to_string(),
+        _ => tail.into_owned(),
+    }
+}
+
+/// Up to the first `max_bytes` of the node's newest log file.
+pub(crate) fn head(log_dir: &Path, max_bytes: u64) -> String {
+    let file = match newest_log_file(log_dir).and_then(|path| File::open(path).ok()) {
+        Some(file) => file,
+        None => return String::new(),
+    };
+
+    let mut bytes = vec![];
+    if file.take(max_bytes).read_to_end(&mut bytes).is_err() {
+        return String::new();
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -9,14 +9,84 @@
 
 use clap::Parser;
 use eyre::Result;
-use sn_launch_tool::Launch;
+use sn_launch_tool::{Join, Launch, List, Pause, Resume, Stop, TailAll};
+use std::str::FromStr;
 use tracing::debug;
+use tracing_subscriber::{
+    filter::{LevelFilter, Targets},
+    fmt::{self, writer::BoxMakeWriter},
+    prelude::*,
+};
+
+/// Tool to launch Safe nodes to form a local single-section network
+///
+/// Currently, this tool runs nodes on localhost (since that's the default if no IP address is given to the nodes)
+#[derive(Debug, Parser)]
+#[clap(version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[clap(flatten)]
+    launch: Launch,
+
+    #[clap(subcommand)]
+    cmd: Option<Cmd>,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Cmd {
+    Join(Box<Join>),
+    Pause(Pause),
+    Resume(Resume),
+    Stop(Stop),
+    TailAll(TailAll),
+    List(List),
+}
 
 fn main() -> Result<()> {
     color_eyre::install()?;
-    tracing_subscriber::fmt::init();
 
-    debug!("Launching Safe nodes...");
+    let cli = Cli::parse_from(Launch::expand_args(std::env::args_os())?);
+    let (writer, filter) = if cli.launch.errors_only() {
+        (
+            BoxMakeWriter::new(std::io::stderr),
+            Targets::new().with_default(LevelFilter::ERROR),
+        )
+    } else if cli.launch.logs_to_stderr() {
+        (
+            BoxMakeWriter::new(std::io::stderr),
+            Targets::new().with_default(LevelFilter::INFO),
+        )
+    } else {
+        (BoxMakeWriter::new(std::io::stdout), rust_log_filter())
+    };
+    tracing_subscriber::registry()
+        .with(fmt::layer().with_writer(writer).with_filter(filter))
+        .with(sn_launch_tool::launch_log_layer())
+        .init();
+
+    match cli.cmd {
+        Some(Cmd::Join(join)) => {
+            debug!("Joining a Safe network...");
+            join.run()
+        }
+        Some(Cmd::Pause(pause)) => pause.run(),
+        Some(Cmd::Resume(resume)) => resume.run(),
+        Some(Cmd::Stop(stop)) => stop.run(),
+        Some(Cmd::TailAll(tail_all)) => tail_all.run(),
+        Some(Cmd::List(list)) => list.run(),
+        None => {
+            debug!("Launching Safe nodes...");
+            cli.launch.run()
+        }
+    }
+}
 
-    Launch::parse().run()
+/// The terminal's filter according to RUST_LOG, INFO level by default.
+fn rust_log_filter() -> Targets {
+    match std::env::var("RUST_LOG") {
+        Ok(rust_log) => Targets::from_str(&rust_log).unwrap_or_else(|error| {
+            eprintln!("Ignoring RUST_LOG={rust_log:?}: {error}");
+            Targets::new().with_default(LevelFilter::INFO)
+        }),
+        Err(_) => Targets::new().with_default(LevelFilter::INFO),
+    }
 }
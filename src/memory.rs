@@ -0,0 +1,83 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{eyre, Result};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use sysinfo::System;
+use tracing::{debug, info, warn};
+
+const MIB: u64 = 1024 * 1024;
+
+/// How often the available memory is checked again while launching is paused.
+const RECOVERY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pauses launching while the system's available memory is below a threshold.
+pub(crate) struct MemoryGuard {
+    system: System,
+    min_free_mib: u64,
+    timeout: Duration,
+}
+
+impl MemoryGuard {
+    pub(crate) fn new(min_free_mib: u64, timeout: Duration) -> Self {
+        Self {
+            system: System::new(),
+            min_free_mib,
+            timeout,
+        }
+    }
+
+    /// Wait until at least the minimum of memory is available to launch `node_name`, failing if
+    /// it isn't within the timeout.
+    pub(crate) fn wait_for_free_memory(&mut self, node_name: &str) -> Result<()> {
+        let available = self.available_mib();
+        if available >= self.min_free_mib {
+            debug!("{} MiB of memory available", available);
+            return Ok(());
+        }
+
+        warn!(
+            "Only {} MiB of memory available, below --min-free-mem of {} MiB: pausing before launching {} for up to {}s",
+            available,
+            self.min_free_mib,
+            node_name,
+            self.timeout.as_secs()
+        );
+        let paused = Instant::now();
+        loop {
+            thread::sleep(RECOVERY_POLL_INTERVAL);
+            let available = self.available_mib();
+            if available >= self.min_free_mib {
+                info!(
+                    "{} MiB of memory available after {:.0}s, resuming the launch",
+                    available,
+                    paused.elapsed().as_secs_f64()
+                );
+                return Ok(());
+            }
+            if paused.elapsed() >= self.timeout {
+                return Err(eyre!(
+                    "Aborting the launch before {}: only {} MiB of memory available after waiting {}s for the --min-free-mem of {} MiB",
+                    node_name,
+                    available,
+                    self.timeout.as_secs(),
+                    self.min_free_mib
+                ));
+            }
+        }
+    }
+
+    fn available_mib(&mut self) -> u64 {
+        self.system.refresh_memory();
+        self.system.available_memory() / MIB
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{eyre, Result, WrapErr};
+use std::{collections::BTreeSet, fs, io, path::Path};
+use tracing::debug;
+
+/// Index of the genesis node.
+pub(crate) const GENESIS_IDX: usize = 1;
+
+/// Prefix of the dirs of nodes started with `join`, followed by a number.
+const JOINED_PREFIX: &str = "joined-";
+
+/// How the nodes' dirs, and so their logs, are named.
+#[derive(Clone, Debug, clap::StructOpt)]
+pub(crate) struct NamingArgs {
+    /// Template for the names of the nodes' dirs, with the placeholders `{prefix}`, `{role}`
+    /// (`genesis` or `node`) and `{index}` (genesis being 1), or `{index:N}` to pad the index
+    /// with zeros to N digits, e.g. `testnet-a-{role}-{index:2}`. By default genesis is named
+    /// `sn-node-genesis` and the other nodes `sn-node-<index>`.
+    #[clap(long, value_parser)]
+    name_template: Option<String>,
+
+    /// Value of the `{prefix}` placeholder of --name-template.
+    #[clap(
+        long,
+        default_value = "sn-node",
+        requires = "name-template",
+        value_parser
+    )]
+    name_prefix: String,
+}
+
+impl NamingArgs {
+    /// Name of the node with index `idx`.
+    pub(crate) fn node_name(&self, idx: usize) -> String {
+        match &self.name_template {
+            Some(template) => render(template, &self.name_prefix, idx),
+            None if idx == GENESIS_IDX => "sn-node-genesis".to_string(),
+            None => format!("sn-node-{idx}"),
+        }
+    }
+
+    /// Index of the node named `name`, if it's named the way this launch names its nodes.
+    pub(crate) fn node_idx(&self, name: &str) -> Option<usize> {
+        if name == self.node_name(GENESIS_IDX) {
+            return Some(GENESIS_IDX);
+        }
+        // the index is one of the runs of digits in the name, possibly zero padded
+        name.split(|c: char| !c.is_ascii_digit())
+            .filter_map(|digits| digits.parse().ok())
+            .find(|&idx| self.node_name(idx) == name)
+    }
+
+    /// Indices of the nodes with a dir in `nodes_dir`, ignoring the entries not named as nodes,
+    /// including the dirs of nodes started with `join`, which don't take up any index.
+    pub(crate) fn existing_node_idxs(&self, nodes_dir: &Path) -> Result<BTreeSet<usize>> {
+        let entries = fs::read_dir(nodes_dir)
+            .wrap_err_with(|| format!("Could not read nodes dir {}", nodes_dir.display()))?;
+        let mut idxs = BTreeSet::new();
+        let mut joined = 0;
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if !entry.path().is_dir() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_joined_node_name(&name) {
+                joined += 1;
+            } else if let Some(idx) = self.node_idx(&name) {
+                let _ = idxs.insert(idx);
+            }
+        }
+        if joined > 0 {
+            debug!("Ignoring {} node(s) started with `join`", joined);
+        }
+        Ok(idxs)
+    }
+
+    /// Fail if the name template is invalid, or doesn't give unique names to nodes `1..=count`.
+    pub(crate) fn validate(&self, count: usize) -> Result<()> {
+        let template = match &self.name_template {
+            Some(template) => template,
+            None => return Ok(()),
+        };
+
+        let placeholders = placeholders(template);
+        if let Some(unknown) = placeholders
+            .iter()
+            .find(|placeholder| placeholder_value(placeholder, "", 0).is_none())
+        {
+            return Err(eyre!(
+                "Unknown placeholder '{{{}}}' in the name template '{}'",
+                unknown,
+                template
+            ));
+        }
+        if !placeholders
+            .iter()
+            .any(|placeholder| placeholder.starts_with("index"))
+        {
+            return Err(eyre!(
+                "The name template '{}' must include the node's {{index}}",
+                template
+            ));
+        }
+
+        let mut names = BTreeSet::new();
+        for idx in 1..=count {
+            let name = self.node_name(idx);
+            if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+                return Err(eyre!(
+                    "The name template '{}' gives node #{} the invalid dir name '{}'",
+                    template,
+                    idx,
+                    name
+                ));
+            }
+            if is_joined_node_name(&name) {
+                return Err(eyre!(
+                    "The name template '{}' gives node #{} the name '{}', which is reserved for nodes started with `join`",
+                    template,
+                    idx,
+                    name
+                ));
+            }
+            if !names.insert(name.clone()) {
+                return Err(eyre!(
+                    "The name template '{}' gives more than one node the name '{}'",
+                    template,
+                    name
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Create a new dir for a node started with `join` in `nodes_dir`, returning its name.
+///
+/// Joined nodes are named `joined-<n>` with the lowest free `n`, which never collides with the
+/// names of launched nodes. The dir is created here to claim the name, even if another `join`
+/// runs at the same time.
+pub(crate) fn claim_joined_node_dir(nodes_dir: &Path) -> Result<String> {
+    fs::create_dir_all(nodes_dir)
+        .wrap_err_with(|| format!("Could not create directory {}", nodes_dir.display()))?;
+
+    for n in 1.. {
+        let name = format!("{JOINED_PREFIX}{n}");
+        let node_dir = nodes_dir.join(&name);
+        match fs::create_dir(&node_dir) {
+            Ok(()) => return Ok(name),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => {
+                return Err(error)
+                    .wrap_err_with(|| format!("Could not create directory {}", node_dir.display()))
+            }
+        }
+    }
+    unreachable!("ran out of names for joined nodes")
+}
+
+/// The name the next node started with `join` in `nodes_dir` would get, without claiming it.
+pub(crate) fn next_joined_node_name(nodes_dir: &Path) -> String {
+    (1..)
+        .map(|n| format!("{JOINED_PREFIX}{n}"))
+        .find(|name| !nodes_dir.join(name).exists())
+        .unwrap_or_else(|| unreachable!("ran out of names for joined nodes"))
+}
+
+/// Whether `name` is the name of a node started with `join`.
+pub(crate) fn is_joined_node_name(name: &str) -> bool {
+    name.strip_prefix(JOINED_PREFIX)
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// The template with its placeholders replaced, leaving unknown ones as they are.
+fn render(template: &str, prefix: &str, idx: usize) -> String {
+    let mut name = String::new();
+    let mut rest = template;
+    while let Some((before, placeholder, after)) = next_placeholder(rest) {
+        name.push_str(before);
+        match placeholder_value(placeholder, prefix, idx) {
+            Some(value) => name.push_str(&value),
+            None => name.push_str(&format!("{{{placeholder}}}")),
+        }
+        rest = after;
+    }
+    name.push_str(rest);
+    name
+}
+
+fn placeholders(template: &str) -> Vec<&str> {
+    let mut placeholders = vec![];
+    let mut rest = template;
+    while let Some((_, placeholder, after)) = next_placeholder(rest) {
+        placeholders.push(placeholder);
+        rest = after;
+    }
+    placeholders
+}
+
+/// The text before the next `{placeholder}`, the placeholder, and the text after it.
+fn next_placeholder(template: &str) -> Option<(&str, &str, &str)> {
+    let (before, rest) = template.split_once('{')?;
+    let (placeholder, after) = rest.split_once('}')?;
+    Some((before, placeholder, after))
+}
+
+fn placeholder_value(placeholder: &str, prefix: &str, idx: usize) -> Option<String> {
+    match placeholder {
+        "prefix" => Some(prefix.to_string()),
+        "role" if idx == GENESIS_IDX => Some("genesis".to_string()),
+        "role" => Some("node".to_string()),
+        "index" => Some(idx.to_string()),
+        _ => {
+            let width: usize = placeholder.strip_prefix("index:")?.parse().ok()?;
+            Some(format!("{idx:0width$}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naming(template: &str) -> NamingArgs {
+        NamingArgs {
+            name_template: Some(template.to_string()),
+            name_prefix: "sn-node".to_string(),
+        }
+    }
+
+    /// The error validating `template` for `count` nodes, which must fail.
+    fn validation_error(template: &str, count: usize) -> String {
+        let error = naming(template).validate(count).unwrap_err();
+        format!("{error:#}")
+    }
+
+    #[test]
+    fn templates_render_every_placeholder() -> Result<()> {
+        let naming = naming("{prefix}-{role}-{index:3}");
+        naming.validate(1000)?;
+        assert_eq!(naming.node_name(1), "sn-node-genesis-001");
+        assert_eq!(naming.node_name(12), "sn-node-node-012");
+        // wider indices aren't truncated, so don't collide
+        assert_eq!(naming.node_name(1000), "sn-node-node-1000");
+        assert_eq!(naming.node_idx("sn-node-node-012"), Some(12));
+        Ok(())
+    }
+
+    #[test]
+    fn templates_without_an_index_are_rejected() {
+        assert!(validation_error("{prefix}-{role}", 3).contains("must include the node's {index}"));
+        assert!(validation_error("node-{index", 3).contains("must include the node's {index}"));
+    }
+
+    #[test]
+    fn unknown_placeholders_are_rejected() {
+        for template in ["{index}-{name}", "{index:x}", "{{index}"] {
+            assert!(
+                validation_error(template, 3).contains("Unknown placeholder"),
+                "'{}' was accepted",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn templates_giving_invalid_dir_names_are_rejected() {
+        assert!(validation_error("{role}/{index}", 2).contains("the invalid dir name 'genesis/1'"));
+        assert!(validation_error(r"{role}\{index}", 2).contains("the invalid dir name"));
+    }
+
+    #[test]
+    fn templates_colliding_with_joined_nodes_are_rejected() {
+        assert!(validation_error("joined-{index}", 2)
+            .contains("gives node #1 the name 'joined-1', which is reserved"));
+        // only names of the joined nodes' form collide with them
+        assert!(naming("joined-{index}-{role}").validate(2).is_ok());
+    }
+
+    #[test]
+    fn default_names_need_no_validation() -> Result<()> {
+        let naming = NamingArgs {
+            name_template: None,
+            name_prefix: "sn-node".to_string(),
+        };
+        naming.validate(3)?;
+        assert_eq!(naming.node_name(GENESIS_IDX), "sn-node-genesis");
+        assert_eq!(naming.node_idx("sn-node-3"), Some(3));
+        assert_eq!(naming.node_idx("joined-3"), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{eyre, Result};
+use std::path::Path;
+
+/// File in the nodes dir recording that latency was injected for the network, so it's removed
+/// along with the network.
+const LATENCY_MARKER_FILE: &str = "injected_latency";
+
+/// Loopback interface the local nodes talk to each other over.
+#[cfg(target_os = "linux")]
+const LOOPBACK_DEVICE: &str = "lo";
+
+/// Fail early, before any node is launched, if artificial latency can't be injected here.
+#[cfg(target_os = "linux")]
+pub(crate) fn ensure_supported() -> Result<()> {
+    let _ = tc(&["qdisc", "show", "dev", LOOPBACK_DEVICE])?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn ensure_supported() -> Result<()> {
+    Err(eyre!(
+        "Injecting latency is only supported on Linux (it relies on `tc` and the netem qdisc)"
+    ))
+}
+
+/// Add `delay_ms` of latency to all traffic on the loopback interface using a netem qdisc,
+/// replacing any left by a previous run, and record it in `nodes_dir`.
+///
+/// This requires `tc` to be installed and enough privileges to change the interface's qdisc.
+#[cfg(target_os = "linux")]
+pub(crate) fn apply_loopback_delay(delay_ms: u64, nodes_dir: &Path) -> Result<()> {
+    use tracing::info;
+
+    let delay = format!("{delay_ms}ms");
+    let _ = tc(&[
+        "qdisc",
+        "replace",
+        "dev",
+        LOOPBACK_DEVICE,
+        "root",
+        "netem",
+        "delay",
+        &delay,
+    ])?;
+    std::fs::write(nodes_dir.join(LATENCY_MARKER_FILE), &delay).map_err(|error| {
+        eyre!("Could not record the latency injected in {LATENCY_MARKER_FILE}: {error}")
+    })?;
+
+    info!(
+        "Injected {delay} of latency on '{LOOPBACK_DEVICE}', until the network is stopped or it's removed with `tc qdisc del dev {LOOPBACK_DEVICE} root netem`"
+    );
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn apply_loopback_delay(_delay_ms: u64, _nodes_dir: &Path) -> Result<()> {
+    ensure_supported()
+}
+
+/// Remove the latency injected for the network in `nodes_dir`, if any.
+pub(crate) fn remove_loopback_delay(nodes_dir: &Path) -> Result<()> {
+    let marker = nodes_dir.join(LATENCY_MARKER_FILE);
+    if !marker.is_file() {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = tc(&["qdisc", "del", "dev", LOOPBACK_DEVICE, "root", "netem"])?;
+        tracing::info!("Removed the latency injected on '{LOOPBACK_DEVICE}'");
+    }
+    std::fs::remove_file(&marker)
+        .map_err(|error| eyre!("Could not remove {}: {error}", marker.display()))
+}
+
+/// Run `tc` with `args`, failing with its stderr if it fails.
+#[cfg(target_os = "linux")]
+fn tc(args: &[&str]) -> Result<std::process::Output> {
+    let output = std::process::Command::new("tc")
+        .args(args)
+        .output()
+        .map_err(|error| eyre!("Failed to run 'tc' with args {args:?}: {error}"))?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "'tc' failed with args {args:?} (status: {}, stderr: {})",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output)
+}
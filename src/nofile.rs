@@ -0,0 +1,77 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::Result;
+
+/// File descriptors each node is estimated to need for its own logs, store and listeners.
+const BASE_FDS_PER_NODE: u64 = 256;
+
+/// File descriptors each node is estimated to need for every other node in the network, for its
+/// connections to it.
+const FDS_PER_PEER: u64 = 4;
+
+/// The open files limit each node is estimated to need in a network of `network_size` nodes.
+pub(crate) fn needed(network_size: usize) -> u64 {
+    BASE_FDS_PER_NODE + FDS_PER_PEER * network_size as u64
+}
+
+/// The soft and hard limits on open files, inherited by the nodes we spawn.
+///
+/// `None` when this isn't supported on the platform.
+#[cfg(unix)]
+pub(crate) fn limits() -> Result<Option<(u64, u64)>> {
+    use eyre::eyre;
+
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: `limit` is a valid `rlimit` for `getrlimit` to write to.
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return Err(eyre!(
+            "Could not query the open files limit: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    #[allow(clippy::unnecessary_cast)] // the field types differ between platforms
+    Ok(Some((limit.rlim_cur as u64, limit.rlim_max as u64)))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn limits() -> Result<Option<(u64, u64)>> {
+    Ok(None)
+}
+
+/// Raise the soft limit on open files of this process, and so of the nodes it spawns from now on,
+/// to `soft`. The hard limit is raised too if it's lower, which requires privileges.
+#[cfg(unix)]
+pub(crate) fn raise(soft: u64) -> Result<()> {
+    use eyre::eyre;
+
+    let (_, hard) = limits()?.unwrap_or((0, 0));
+    let limit = libc::rlimit {
+        rlim_cur: soft as libc::rlim_t,
+        rlim_max: hard.max(soft) as libc::rlim_t,
+    };
+    // SAFETY: `setrlimit` only reads `limit`.
+    if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) } != 0 {
+        return Err(eyre!(
+            "Could not raise the open files limit to {}: {}",
+            soft,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn raise(_soft: u64) -> Result<()> {
+    Ok(())
+}
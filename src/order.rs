@@ -0,0 +1,194 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::intervals::SplitMix64;
+use eyre::{eyre, Result};
+use std::{collections::BTreeSet, ops::RangeInclusive};
+
+/// The order to launch the joining nodes in, as a list of node indices.
+#[derive(Clone, Debug)]
+pub(crate) struct LaunchOrder(Vec<usize>);
+
+impl LaunchOrder {
+    /// Parse a comma separated list of node indices and ranges, e.g. `8-15,2-7`. A range whose
+    /// end is lower than its start is launched in descending order.
+    pub(crate) fn parse(spec: &str) -> Result<Self, String> {
+        let mut indices = vec![];
+        for part in spec.split(',').map(str::trim) {
+            let (start, end) = match part.split_once('-') {
+                Some((start, end)) => (parse_index(start)?, parse_index(end)?),
+                None => {
+                    let idx = parse_index(part)?;
+                    (idx, idx)
+                }
+            };
+            if start <= end {
+                indices.extend(start..=end);
+            } else {
+                indices.extend((end..=start).rev());
+            }
+        }
+
+        Ok(Self(indices))
+    }
+
+    /// The `node_ids` being launched in this order, failing unless it lists each of them exactly
+    /// once.
+    pub(crate) fn schedule(&self, node_ids: &RangeInclusive<usize>) -> Result<Vec<usize>> {
+        let mut seen = BTreeSet::new();
+        for idx in &self.0 {
+            if !node_ids.contains(idx) {
+                return Err(eyre!(
+                    "The launch order includes node #{}, which isn't one of the nodes being launched ({}-{})",
+                    idx,
+                    node_ids.start(),
+                    node_ids.end()
+                ));
+            }
+            if !seen.insert(*idx) {
+                return Err(eyre!(
+                    "The launch order includes node #{} more than once",
+                    idx
+                ));
+            }
+        }
+
+        let missing: Vec<_> = node_ids
+            .clone()
+            .filter(|idx| !seen.contains(idx))
+            .map(|idx| idx.to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(eyre!(
+                "The launch order doesn't include node(s) {}",
+                missing.join(", ")
+            ));
+        }
+
+        Ok(self.0.clone())
+    }
+}
+
+/// The order `stop` stops a network's nodes in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum StopOrder {
+    /// The order they were launched in, genesis first.
+    Launch,
+    /// The reverse of the order they were launched in, genesis last.
+    Reverse,
+    /// Genesis, then the other nodes in reverse launch order.
+    GenesisFirst,
+    /// The other nodes in launch order, then genesis.
+    GenesisLast,
+}
+
+impl StopOrder {
+    /// `nodes`, listed in launch order, in this order. `has_genesis` tells whether the first of
+    /// them is genesis, rather than a node joined to a network launched elsewhere.
+    pub(crate) fn arrange<T>(self, mut nodes: Vec<T>, has_genesis: bool) -> Vec<T> {
+        match self {
+            Self::Launch => {}
+            Self::Reverse => nodes.reverse(),
+            Self::GenesisFirst if has_genesis && !nodes.is_empty() => nodes[1..].reverse(),
+            Self::GenesisFirst => nodes.reverse(),
+            Self::GenesisLast if has_genesis && !nodes.is_empty() => nodes.rotate_left(1),
+            Self::GenesisLast => {}
+        }
+        nodes
+    }
+}
+
+/// Shuffle the launch order with a generator seeded with `seed`, so the same seed gives the same
+/// order.
+pub(crate) fn shuffle(order: &mut [usize], seed: u64) {
+    let mut rng = SplitMix64(seed);
+    // Fisher-Yates
+    for i in (1..order.len()).rev() {
+        let j = (rng.next() % (i as u64 + 1)) as usize;
+        order.swap(i, j);
+    }
+}
+
+fn parse_index(value: &str) -> Result<usize, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{value}' is not a valid node index"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(spec: &str) -> Result<LaunchOrder> {
+        LaunchOrder::parse(spec).map_err(|error| eyre!(error))
+    }
+
+    #[test]
+    fn ranges_are_listed_in_either_direction() -> Result<()> {
+        let order = parse("5-7, 4-2,8")?;
+        assert_eq!(order.schedule(&(2..=8))?, [5, 6, 7, 4, 3, 2, 8]);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_indices_are_rejected() {
+        for spec in ["", "3,x", "2-", "-4", "1.5"] {
+            assert!(LaunchOrder::parse(spec).is_err(), "'{}' was parsed", spec);
+        }
+        assert_eq!(
+            LaunchOrder::parse("2,x").unwrap_err(),
+            "'x' is not a valid node index"
+        );
+    }
+
+    #[test]
+    fn duplicate_nodes_are_rejected() -> Result<()> {
+        let error = parse("2-4,3")?.schedule(&(2..=4)).unwrap_err();
+        assert!(format!("{error:#}").contains("includes node #3 more than once"));
+        Ok(())
+    }
+
+    #[test]
+    fn nodes_not_being_launched_are_rejected() -> Result<()> {
+        let error = parse("2-5")?.schedule(&(2..=4)).unwrap_err();
+        assert!(format!("{error:#}").contains("includes node #5, which isn't one of the nodes"));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_nodes_are_rejected() -> Result<()> {
+        let error = parse("4,2")?.schedule(&(2..=5)).unwrap_err();
+        assert!(format!("{error:#}").contains("doesn't include node(s) 3, 5"));
+        Ok(())
+    }
+
+    #[test]
+    fn nodes_are_stopped_in_the_order_asked_for() {
+        let nodes = vec![1, 2, 3, 4];
+        let arranged = |order: StopOrder, has_genesis| order.arrange(nodes.clone(), has_genesis);
+
+        assert_eq!(arranged(StopOrder::Launch, true), [1, 2, 3, 4]);
+        assert_eq!(arranged(StopOrder::Reverse, true), [4, 3, 2, 1]);
+        assert_eq!(arranged(StopOrder::GenesisFirst, true), [1, 4, 3, 2]);
+        assert_eq!(arranged(StopOrder::GenesisLast, true), [2, 3, 4, 1]);
+    }
+
+    #[test]
+    fn stop_orders_without_genesis_keep_the_launch_order_of_the_other_nodes() {
+        let nodes = vec![2, 3, 4];
+        let arranged = |order: StopOrder| order.arrange(nodes.clone(), false);
+
+        assert_eq!(arranged(StopOrder::GenesisFirst), [4, 3, 2]);
+        assert_eq!(arranged(StopOrder::GenesisLast), [2, 3, 4]);
+        assert!(StopOrder::GenesisFirst
+            .arrange(Vec::<usize>::new(), true)
+            .is_empty());
+    }
+}
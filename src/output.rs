@@ -0,0 +1,166 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{process::Restarts, tags::NodeTags, LaunchedNode};
+use eyre::{Result, WrapErr};
+use serde::Serialize;
+use std::{
+    fmt::Write,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// What was launched, for --summary-json.
+#[derive(Serialize)]
+struct NetworkSummary<'a> {
+    node_count: usize,
+    nodes: Vec<NodeSummary<'a>>,
+    /// Where the network contacts were copied to for clients.
+    contacts_files: &'a [PathBuf],
+}
+
+#[derive(Serialize)]
+struct NodeSummary<'a> {
+    name: &'a str,
+    root_dir: PathBuf,
+    log_dir: PathBuf,
+    addr: Option<SocketAddr>,
+    /// The node's tag with --tag-nodes.
+    tag: Option<String>,
+    /// How many times the node was restarted, e.g. by --rolling-restart.
+    restarts: u32,
+    /// How the node last exited, e.g. `signal 15`, if it ever did.
+    last_exit: Option<String>,
+}
+
+/// A node's entry in --addrs-json.
+#[derive(Serialize)]
+struct NodeAddr<'a> {
+    index: usize,
+    name: &'a str,
+    addr: SocketAddr,
+}
+
+/// Write a hosts-file style `<ip> <node name>` line for each node whose address is known.
+pub(crate) fn write_hosts_file(path: &Path, nodes: &[LaunchedNode]) -> Result<()> {
+    let mut hosts = "# Node addresses written by sn_launch_tool\n".to_string();
+    for node in nodes {
+        if let Some(addr) = node.addr {
+            let _ = writeln!(hosts, "{} {}", addr.ip(), node.name);
+        }
+    }
+
+    fs::write(path, hosts)
+        .wrap_err_with(|| format!("Could not write hosts file {}", path.display()))?;
+    info!("Node hosts written to {}", path.display());
+
+    Ok(())
+}
+
+/// Write a JSON array with the index, name and address of each node whose address is known, e.g.
+/// `[{"index": 1, "name": "sn-node-genesis", "addr": "127.0.0.1:12000"}]`, to `path`, or to
+/// stdout if it's `-`.
+pub(crate) fn write_addrs_json(path: &Path, nodes: &[LaunchedNode]) -> Result<()> {
+    let entries: Vec<_> = nodes
+        .iter()
+        .filter_map(|node| {
+            Some(NodeAddr {
+                index: node.idx,
+                name: &node.name,
+                addr: node.addr?,
+            })
+        })
+        .collect();
+    let mut json =
+        serde_json::to_string_pretty(&entries).wrap_err("Could not serialize node addresses")?;
+    json.push('\n');
+
+    if path == Path::new("-") {
+        print!("{json}");
+        return Ok(());
+    }
+    fs::write(path, json)
+        .wrap_err_with(|| format!("Could not write node addresses {}", path.display()))?;
+    info!("Node addresses written to {}", path.display());
+
+    Ok(())
+}
+
+/// Write a JSON summary of the launched `nodes`, whose dirs are in `nodes_dir`, tagged with
+/// `node_tags` if they are, with the restarts recorded for them, and of the `contacts_files` the
+/// network contacts were copied to, to `path`.
+pub(crate) fn write_summary_json(
+    path: &Path,
+    nodes: &[LaunchedNode],
+    nodes_dir: &Path,
+    contacts_files: &[PathBuf],
+    node_tags: Option<&NodeTags>,
+) -> Result<()> {
+    let summary = NetworkSummary {
+        node_count: nodes.len(),
+        nodes: nodes
+            .iter()
+            .map(|node| {
+                // the nodes keep their data and logs in the same dir
+                let node_dir = nodes_dir.join(&node.name);
+                let restarts = Restarts::load(&node_dir);
+                NodeSummary {
+                    name: &node.name,
+                    root_dir: node_dir.clone(),
+                    log_dir: node_dir,
+                    addr: node.addr,
+                    tag: node_tags.map(|node_tags| node_tags.tag(node.idx)),
+                    restarts: restarts.count,
+                    last_exit: restarts.last_exit,
+                }
+            })
+            .collect(),
+        contacts_files,
+    };
+    let mut json = serde_json::to_string_pretty(&summary)
+        .wrap_err("Could not serialize the network summary")?;
+    json.push('\n');
+
+    fs::write(path, json)
+        .wrap_err_with(|| format!("Could not write network summary {}", path.display()))?;
+    info!("Network summary written to {}", path.display());
+
+    Ok(())
+}
+
+/// Shell syntax used for `--export-env` statements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum ExportShell {
+    /// `export NAME='value'`
+    Sh,
+    /// `$env:NAME = 'value'`
+    Powershell,
+}
+
+impl ExportShell {
+    pub(crate) fn platform_default() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::Powershell
+        } else {
+            Self::Sh
+        }
+    }
+}
+
+/// Print a statement setting each of `vars` to stdout, for the shell to `eval`.
+pub(crate) fn print_env_exports(vars: &[(&str, String)], shell: ExportShell) {
+    for (name, value) in vars {
+        match shell {
+            ExportShell::Sh => println!("export {}='{}'", name, value.replace('\'', r"'\''")),
+            ExportShell::Powershell => println!("$env:{} = '{}'", name, value.replace('\'', "''")),
+        }
+    }
+}
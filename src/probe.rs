@@ -0,0 +1,229 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{eyre, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Maximum number of binaries probed at the same time.
+const MAX_CONCURRENT_PROBES: usize = 4;
+
+/// How long a single run of a binary being probed may take before it's killed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often a probe is checked for having exited.
+const PROBE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Oldest sn_node taking the args the nodes are launched with, e.g. genesis' address with
+/// `--first`.
+pub(crate) const MIN_SN_NODE_VERSION: (u64, u64, u64) = (0, 75, 0);
+
+/// What a sn_node binary reports about itself.
+pub(crate) struct BinaryInfo {
+    pub(crate) version: String,
+    pub(crate) flags: BTreeSet<String>,
+}
+
+/// Probe the version and supported flags of each of `binaries` concurrently, with at most
+/// [`MAX_CONCURRENT_PROBES`] at a time, returning each binary's result separately.
+pub(crate) fn probe_binaries(binaries: &[&Path]) -> BTreeMap<PathBuf, Result<BinaryInfo>> {
+    let pending = Mutex::new(binaries.iter());
+    let results = Mutex::new(BTreeMap::new());
+
+    thread::scope(|scope| {
+        for _ in 0..binaries.len().min(MAX_CONCURRENT_PROBES) {
+            scope.spawn(|| loop {
+                let binary = match pending.lock().map(|mut pending| pending.next()) {
+                    Ok(Some(binary)) => binary,
+                    _ => break,
+                };
+                let result = probe_binary(binary);
+                if let Ok(mut results) = results.lock() {
+                    let _ = results.insert(binary.to_path_buf(), result);
+                }
+            });
+        }
+    });
+
+    results.into_inner().unwrap_or_default()
+}
+
+/// Flags (`--name`) listed in a binary's `--help` output.
+pub(crate) fn parse_help_flags(help: &[u8]) -> BTreeSet<String> {
+    String::from_utf8_lossy(help)
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .filter(|word| word.starts_with("--"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The `major.minor.patch` numbers in a binary's version output, e.g. `sn_node 0.58.3`.
+pub(crate) fn semver(version: &str) -> Option<(u64, u64, u64)> {
+    version.split_whitespace().find_map(|word| {
+        // ignore any pre-release or build metadata
+        let core = word.trim_start_matches('v').split(['-', '+']).next()?;
+        let mut numbers = core.split('.').map(|number| number.parse().ok());
+        match (
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+        ) {
+            (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) => {
+                Some((major, minor, patch))
+            }
+            _ => None,
+        }
+    })
+}
+
+/// Fail unless a binary's version output names a version at least [`MIN_SN_NODE_VERSION`].
+pub(crate) fn ensure_supported_version(version: &str) -> Result<()> {
+    let (major, minor, patch) = MIN_SN_NODE_VERSION;
+    match semver(version) {
+        Some(semver) if semver >= MIN_SN_NODE_VERSION => Ok(()),
+        Some(_) => Err(eyre!(
+            "'{}' is older than sn_node {}.{}.{}, the oldest supported version, so its nodes would likely fail to start: use a newer sn_node, or pass --skip-version-check to launch it anyway",
+            version,
+            major,
+            minor,
+            patch
+        )),
+        None => Err(eyre!(
+            "Could not tell the version of '{}', which must be at least sn_node {}.{}.{}: pass --skip-version-check to launch it anyway",
+            version,
+            major,
+            minor,
+            patch
+        )),
+    }
+}
+
+/// Whether nodes of these versions are expected to work together, going by semver: before 1.0
+/// only within the same minor version, then within the same major version.
+pub(crate) fn compatible(a: (u64, u64, u64), b: (u64, u64, u64)) -> bool {
+    match (a, b) {
+        ((0, a_minor, _), (0, b_minor, _)) => a_minor == b_minor,
+        ((a_major, ..), (b_major, ..)) => a_major == b_major,
+    }
+}
+
+fn probe_binary(binary: &Path) -> Result<BinaryInfo> {
+    let version = run_with_timeout(binary, "-V")?;
+    let help = run_with_timeout(binary, "--help")?;
+
+    Ok(BinaryInfo {
+        version: String::from_utf8_lossy(&version).trim().to_string(),
+        flags: parse_help_flags(&help),
+    })
+}
+
+fn run_with_timeout(binary: &Path, arg: &str) -> Result<Vec<u8>> {
+    let mut child = Command::new(binary)
+        .arg(arg)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|error| eyre!("Failed to run with '{arg}': {error}"))?;
+
+    // read the output while waiting, so a chatty binary can't block on a full pipe
+    let mut stdout = child.stdout.take();
+    let reader = thread::spawn(move || {
+        let mut output = vec![];
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = stdout.read_to_end(&mut output);
+        }
+        output
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= PROBE_TIMEOUT {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(eyre!(
+                "Timed out after {}s running with '{arg}'",
+                PROBE_TIMEOUT.as_secs()
+            ));
+        }
+        thread::sleep(PROBE_POLL_INTERVAL);
+    };
+
+    let output = reader.join().unwrap_or_default();
+    if status.success() {
+        Ok(output)
+    } else {
+        Err(eyre!("Exited with {status} when run with '{arg}'"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_found_in_the_version_output() {
+        assert_eq!(semver("sn_node 0.80.3"), Some((0, 80, 3)));
+        assert_eq!(semver("sn_node v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(semver("sn_node 0.80.0-alpha.1"), Some((0, 80, 0)));
+        assert_eq!(semver("sn_node 0.80.0+build.5"), Some((0, 80, 0)));
+        assert_eq!(semver("sn_node 0.80.0 (git abc123)"), Some((0, 80, 0)));
+    }
+
+    #[test]
+    fn incomplete_versions_are_not_found() {
+        assert_eq!(semver("sn_node 0.80"), None);
+        assert_eq!(semver("sn_node 0.80.1.2"), None);
+        assert_eq!(semver("sn_node"), None);
+        assert_eq!(semver(""), None);
+    }
+
+    #[test]
+    fn only_versions_from_the_oldest_supported_are_accepted() {
+        let (major, minor, patch) = MIN_SN_NODE_VERSION;
+        assert!(ensure_supported_version(&format!("sn_node {major}.{minor}.{patch}")).is_ok());
+        assert!(
+            ensure_supported_version(&format!("sn_node v{major}.{minor}.{patch}-rc.1")).is_ok()
+        );
+        assert!(ensure_supported_version("sn_node 1.0.0").is_ok());
+
+        let error = ensure_supported_version("sn_node 0.74.9").unwrap_err();
+        assert!(format!("{error:#}").contains("is older than sn_node 0.75.0"));
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let error = ensure_supported_version("sn_node 0.80").unwrap_err();
+        assert!(format!("{error:#}").contains("Could not tell the version of 'sn_node 0.80'"));
+    }
+
+    #[test]
+    fn versions_before_1_0_are_only_compatible_within_a_minor_version() {
+        assert!(compatible((0, 80, 0), (0, 80, 7)));
+        assert!(!compatible((0, 80, 0), (0, 81, 0)));
+        assert!(!compatible((0, 80, 0), (1, 80, 0)));
+    }
+
+    #[test]
+    fn versions_from_1_0_are_compatible_within_a_major_version() {
+        assert!(compatible((1, 0, 0), (1, 4, 2)));
+        assert!(!compatible((1, 4, 2), (2, 4, 2)));
+    }
+}
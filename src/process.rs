@@ -0,0 +1,407 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{eyre, Result, WrapErr};
+use std::{
+    fs,
+    path::Path,
+    process::{Child, Command, ExitStatus},
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// How often a stopping process is checked for having exited.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// File in each node's dir recording the PID of its process.
+pub(crate) const PID_FILE: &str = "sn_node.pid";
+
+/// File in each node's dir recording how many times the node was restarted and how its process
+/// last exited, as `<restarts> <last exit>`, e.g. `2 signal 15`.
+pub(crate) const RESTARTS_FILE: &str = "sn_node.restarts";
+
+/// File in the nodes dir listing the PIDs of all its nodes, one `<name> <pid>` per line.
+pub(crate) const NETWORK_PIDS_FILE: &str = "network.pids";
+
+/// Record the PID of the node `node_name` running in `nodes_dir`, for later invocations of the
+/// tool and external tooling: in its node dir, and in the nodes dir's list of PIDs.
+pub(crate) fn write_pid(nodes_dir: &Path, node_name: &str, pid: u32) -> Result<()> {
+    write_atomically(&nodes_dir.join(node_name).join(PID_FILE), &pid.to_string())?;
+
+    let mut pids = read_network_pids(nodes_dir).unwrap_or_default();
+    match pids.iter_mut().find(|(name, _)| name == node_name) {
+        Some((_, recorded)) => *recorded = pid,
+        None => pids.push((node_name.to_string(), pid)),
+    }
+    let list: String = pids
+        .iter()
+        .map(|(name, pid)| format!("{name} {pid}\n"))
+        .collect();
+    write_atomically(&nodes_dir.join(NETWORK_PIDS_FILE), &list)
+}
+
+/// The name and PID of each node listed in the nodes dir, in the order they were launched.
+pub(crate) fn read_network_pids(nodes_dir: &Path) -> Result<Vec<(String, u32)>> {
+    let path = nodes_dir.join(NETWORK_PIDS_FILE);
+    let list = fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Could not read PIDs file {}", path.display()))?;
+    list.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, pid) = line
+                .rsplit_once(' ')
+                .ok_or_else(|| eyre!("Invalid line '{}' in {}", line, path.display()))?;
+            let pid = pid
+                .parse()
+                .wrap_err_with(|| format!("Invalid PID in {}", path.display()))?;
+            Ok((name.to_string(), pid))
+        })
+        .collect()
+}
+
+/// Write `contents` to a temporary file next to `path` and rename it into place, so readers never
+/// see it half-written.
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".tmp-{}", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+    fs::write(&tmp_path, contents)
+        .and_then(|()| fs::rename(&tmp_path, path))
+        .wrap_err_with(|| format!("Could not write {}", path.display()))
+}
+
+/// The PID recorded for the node in `node_dir` when it was launched.
+pub(crate) fn read_pid(node_dir: &Path) -> Result<u32> {
+    let path = node_dir.join(PID_FILE);
+    let pid = fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Could not read PID file {}", path.display()))?;
+    pid.trim()
+        .parse()
+        .wrap_err_with(|| format!("Invalid PID file {}", path.display()))
+}
+
+/// How many times a node was restarted, and how its process last exited, as recorded in its dir.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Restarts {
+    pub(crate) count: u32,
+    /// See [`exit_reason`], if the node ever exited.
+    pub(crate) last_exit: Option<String>,
+}
+
+impl Restarts {
+    /// The record in `node_dir`, which is empty if the node was never restarted nor exited.
+    pub(crate) fn load(node_dir: &Path) -> Self {
+        let record = fs::read_to_string(node_dir.join(RESTARTS_FILE)).unwrap_or_default();
+        let (count, last_exit) = record.trim().split_once(' ').unwrap_or((record.trim(), ""));
+        Self {
+            count: count.parse().unwrap_or(0),
+            last_exit: Some(last_exit.to_string()).filter(|last_exit| !last_exit.is_empty()),
+        }
+    }
+
+    /// Record in `node_dir` that its node exited with `status`.
+    pub(crate) fn record_exit(&mut self, node_dir: &Path, status: ExitStatus) -> Result<()> {
+        self.last_exit = Some(exit_reason(status));
+        self.save(node_dir)
+    }
+
+    /// Record in `node_dir` that its node was restarted.
+    pub(crate) fn record_restart(&mut self, node_dir: &Path) -> Result<()> {
+        self.count += 1;
+        self.save(node_dir)
+    }
+
+    fn save(&self, node_dir: &Path) -> Result<()> {
+        let record = match &self.last_exit {
+            Some(last_exit) => format!("{} {}\n", self.count, last_exit),
+            None => format!("{}\n", self.count),
+        };
+        write_atomically(&node_dir.join(RESTARTS_FILE), &record)
+    }
+}
+
+/// Whether the process was terminated by a signal or exited with a code, e.g. `signal 15` or
+/// `code 1`.
+pub(crate) fn exit_reason(status: ExitStatus) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return format!("signal {signal}");
+        }
+    }
+
+    match status.code() {
+        Some(code) => format!("code {code}"),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Ask the process to terminate gracefully (SIGTERM on Unix).
+#[cfg(unix)]
+pub(crate) fn terminate(pid: u32) -> Result<()> {
+    signal(pid, libc::SIGTERM)
+}
+
+/// Terminate the process with `TerminateProcess`. Windows has no graceful equivalent of SIGTERM
+/// for console processes.
+#[cfg(windows)]
+pub(crate) fn terminate(pid: u32) -> Result<()> {
+    use windows_sys::Win32::{
+        Foundation::CloseHandle,
+        System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE},
+    };
+
+    // SAFETY: the handle is only used once checked to be valid, and closed right after.
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+        if handle.is_null() {
+            return Err(eyre!(
+                "Could not open PID {pid}: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let terminated = TerminateProcess(handle, 1) != 0;
+        let error = std::io::Error::last_os_error();
+        let _ = CloseHandle(handle);
+        if terminated {
+            Ok(())
+        } else {
+            Err(eyre!("Failed to terminate PID {pid}: {error}"))
+        }
+    }
+}
+
+/// Kill the process outright (SIGKILL on Unix).
+#[cfg(unix)]
+pub(crate) fn kill(pid: u32) -> Result<()> {
+    signal(pid, libc::SIGKILL)
+}
+
+#[cfg(windows)]
+pub(crate) fn kill(pid: u32) -> Result<()> {
+    terminate(pid)
+}
+
+/// Freeze the process without killing it (SIGSTOP on Unix).
+#[cfg(unix)]
+pub(crate) fn pause(pid: u32) -> Result<()> {
+    signal(pid, libc::SIGSTOP)
+}
+
+/// Resume a process frozen with [`pause`] (SIGCONT on Unix).
+#[cfg(unix)]
+pub(crate) fn resume(pid: u32) -> Result<()> {
+    signal(pid, libc::SIGCONT)
+}
+
+#[cfg(windows)]
+pub(crate) fn pause(_pid: u32) -> Result<()> {
+    Err(eyre!("Pausing nodes is not supported on Windows yet"))
+}
+
+#[cfg(windows)]
+pub(crate) fn resume(_pid: u32) -> Result<()> {
+    Err(eyre!("Resuming nodes is not supported on Windows yet"))
+}
+
+/// Whether a process with this PID is running, if that can be told on this platform.
+#[cfg(unix)]
+pub(crate) fn is_alive(pid: u32) -> Option<bool> {
+    use std::convert::TryFrom;
+
+    let pid = libc::pid_t::try_from(pid).ok()?;
+    // SAFETY: signal 0 only checks whether `pid` exists and may be signalled.
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(!is_zombie(pid));
+    }
+    // the process exists, but belongs to another user
+    Some(std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+}
+
+/// Whether the process has exited, but not been reaped yet by its parent, which may take a while
+/// for orphaned nodes under a minimal init, e.g. in containers.
+#[cfg(target_os = "linux")]
+fn is_zombie(pid: libc::pid_t) -> bool {
+    // the state follows the parenthesised command name, which may itself contain spaces
+    fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| {
+            let (_, after_name) = stat.rsplit_once(')')?;
+            after_name
+                .split_whitespace()
+                .next()
+                .map(|state| state == "Z")
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn is_zombie(_pid: libc::pid_t) -> bool {
+    false
+}
+
+#[cfg(windows)]
+pub(crate) fn is_alive(pid: u32) -> Option<bool> {
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, GetLastError, ERROR_ACCESS_DENIED, WAIT_TIMEOUT},
+        System::Threading::{OpenProcess, WaitForSingleObject, PROCESS_SYNCHRONIZE},
+    };
+
+    // SAFETY: the handle is only used once checked to be valid, and closed right after.
+    unsafe {
+        let handle = OpenProcess(PROCESS_SYNCHRONIZE, 0, pid);
+        if handle.is_null() {
+            // the process exists, but belongs to another user
+            return Some(GetLastError() == ERROR_ACCESS_DENIED);
+        }
+        // a process' handle is signalled once it has exited
+        let running = WaitForSingleObject(handle, 0) == WAIT_TIMEOUT;
+        let _ = CloseHandle(handle);
+        Some(running)
+    }
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, signal: libc::c_int) -> Result<()> {
+    use std::convert::TryFrom;
+
+    let pid = libc::pid_t::try_from(pid).map_err(|_| eyre!("Invalid PID {pid}"))?;
+    // SAFETY: `kill` has no memory safety preconditions, it only sends a signal to `pid`.
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "Failed to send signal {signal} to PID {pid}: {}",
+            std::io::Error::last_os_error()
+        ))
+    }
+}
+
+/// Have `cmd` start its process in a new session, detached from our controlling terminal, so it
+/// isn't hung up when the terminal is closed.
+#[cfg(unix)]
+pub(crate) fn detach(cmd: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: `setsid` is async-signal-safe, so it can be called between fork and exec.
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Have `cmd` start its process without a console, in a new process group, so it isn't tied to
+/// our console.
+#[cfg(windows)]
+pub(crate) fn detach(cmd: &mut Command) {
+    use std::os::windows::process::CommandExt;
+
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    cmd.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+/// Wait up to `grace` for the processes, which needn't be our children, to exit, returning the
+/// ones which may still be running.
+pub(crate) fn wait_for_exits(pids: &[u32], grace: Duration) -> Vec<u32> {
+    let started = Instant::now();
+    loop {
+        let running: Vec<_> = pids
+            .iter()
+            .copied()
+            .filter(|&pid| is_alive(pid) != Some(false))
+            .collect();
+        if running.is_empty() || started.elapsed() >= grace {
+            return running;
+        }
+        thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+/// Kill each of the named child processes, then wait up to `timeout` for them all to exit, as a
+/// node may take a while to, e.g. flushing its state, failing with the names of those which still
+/// haven't.
+pub(crate) fn kill_children<'c>(
+    children: impl IntoIterator<Item = (&'c str, &'c mut Child)>,
+    timeout: Duration,
+) -> Result<()> {
+    let mut exiting = vec![];
+    for (name, child) in children {
+        // fails if it has been reaped already, which try_wait tells below
+        if let Err(error) = child.kill() {
+            debug!("Could not kill {} (PID {}): {:?}", name, child.id(), error);
+        }
+        exiting.push((name, child));
+    }
+
+    let started = Instant::now();
+    loop {
+        exiting.retain_mut(|(name, child)| match child.try_wait() {
+            Ok(Some(status)) => {
+                debug!("{} exited ({})", name, status);
+                false
+            }
+            Ok(None) => true,
+            Err(error) => {
+                debug!("Could not tell whether {} exited: {:?}", name, error);
+                true
+            }
+        });
+        if exiting.is_empty() {
+            return Ok(());
+        }
+        if started.elapsed() >= timeout {
+            let names: Vec<_> = exiting.iter().map(|(name, _)| *name).collect();
+            return Err(eyre!(
+                "{} nodes were still running {}ms after being killed: {}",
+                names.len(),
+                timeout.as_millis(),
+                names.join(", ")
+            ));
+        }
+        thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+/// Gracefully stop a child process, killing it if it hasn't exited after `grace`.
+pub(crate) fn stop_child(child: &mut Child, grace: Duration) -> Result<ExitStatus> {
+    if let Some(status) = child.try_wait()? {
+        return Ok(status);
+    }
+
+    if let Err(error) = terminate(child.id()) {
+        debug!(
+            "Could not terminate PID {} gracefully: {:?}",
+            child.id(),
+            error
+        );
+    }
+
+    let started = Instant::now();
+    while started.elapsed() < grace {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        thread::sleep(EXIT_POLL_INTERVAL);
+    }
+
+    warn!(
+        "PID {} didn't exit within {}s, killing it",
+        child.id(),
+        grace.as_secs()
+    );
+    child.kill()?;
+    Ok(child.wait()?)
+}
@@ -0,0 +1,119 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use clap::ValueEnum;
+use std::ffi::OsString;
+
+/// A named bundle of launch flags for a common scenario, for --profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum Profile {
+    FastLocal,
+    Stress,
+    Profiling,
+}
+
+impl Profile {
+    /// The flags the profile stands for.
+    fn args(self) -> &'static [&'static str] {
+        match self {
+            Self::FastLocal => &[
+                "--local",
+                "--interval",
+                "10",
+                "--liveness-timeout-msec",
+                "0",
+            ],
+            Self::Stress => &[
+                "--local",
+                "--num-nodes",
+                "50",
+                "--auto-raise-nofile",
+                "--readiness",
+                "log-marker",
+            ],
+            Self::Profiling => &["--local", "--flame", "-yy"],
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::FastLocal => "a small local network launched as quickly as possible",
+            Self::Stress => "a large local network, each node waited for before the next",
+            Self::Profiling => "a local network of nodes run with flame graphs and verbose logs",
+        }
+    }
+}
+
+/// `args` with the flags of the --profile given in them, if any, inserted right after the program
+/// name, so that flags given explicitly override them.
+pub(crate) fn expand(args: Vec<OsString>) -> Vec<OsString> {
+    // profiles are only for launching, not for the subcommands, which clap will report
+    if is_subcommand(&args) {
+        return args;
+    }
+
+    // an invalid profile is left for clap to report
+    let profile =
+        flag_value(&args, "--profile").and_then(|name| Profile::from_str(&name, false).ok());
+
+    match profile {
+        Some(profile) => {
+            let mut args = args.into_iter();
+            args.next()
+                .into_iter()
+                .chain(profile.args().iter().map(OsString::from))
+                .chain(args)
+                .collect()
+        }
+        None => args,
+    }
+}
+
+/// Whether the command line `args` run one of the subcommands rather than a launch.
+pub(crate) fn is_subcommand(args: &[OsString]) -> bool {
+    args.get(1)
+        .is_some_and(|arg| !arg.to_string_lossy().starts_with('-'))
+}
+
+/// The value of the last occurrence of the long `flag` in the command line `args`, as either
+/// `--flag value` or `--flag=value`, ignoring the args passed on to the nodes.
+pub(crate) fn flag_value(args: &[OsString], flag: &str) -> Option<String> {
+    let prefix = format!("{flag}=");
+    args.iter()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy())
+        .take_while(|arg| arg != "--")
+        .scan(false, |value_next, arg| {
+            let value = if *value_next {
+                Some(arg.to_string())
+            } else {
+                arg.strip_prefix(&prefix).map(str::to_string)
+            };
+            *value_next = arg == flag;
+            Some(value)
+        })
+        .flatten()
+        .last()
+}
+
+/// Print each profile with its flags, for --list-profiles.
+pub(crate) fn print_list() {
+    for profile in Profile::value_variants() {
+        let name = profile
+            .to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default();
+        println!(
+            "{}: {}\n    {}",
+            name,
+            profile.description(),
+            profile.args().join(" ")
+        );
+    }
+}
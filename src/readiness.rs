@@ -0,0 +1,260 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{intervals::Intervals, logs, LaunchedNode};
+use eyre::{eyre, Result};
+use std::{
+    ffi::OsString,
+    fs, io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, trace};
+
+/// How long to wait between runs of a readiness command which hasn't succeeded yet.
+const READY_CMD_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often a node is checked for being ready by the strategies which poll for it.
+pub(crate) const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How the launcher decides a node is ready, and the next one can be launched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum Readiness {
+    /// Wait for --interval, whatever the node is doing.
+    Sleep,
+    /// Wait for --readiness-file to appear in the node's dir.
+    File,
+    /// Wait for --readiness-log-marker to appear in the node's logs.
+    LogMarker,
+    /// Wait for the node's discovered address to be reachable.
+    Connect,
+    /// Run --readiness-cmd until it succeeds.
+    Command,
+}
+
+/// A way of waiting for a just launched node to be ready.
+pub(crate) trait ReadinessStrategy {
+    /// Block until `node`, whose dir is `node_dir`, is ready, failing if it doesn't get there.
+    fn wait_until_ready(&mut self, node: &LaunchedNode, node_dir: &Path) -> Result<()>;
+}
+
+/// Wait for the next of the launch intervals.
+pub(crate) struct FixedSleep<'a> {
+    pub(crate) intervals: &'a mut Intervals,
+}
+
+impl ReadinessStrategy for FixedSleep<'_> {
+    fn wait_until_ready(&mut self, node: &LaunchedNode, _node_dir: &Path) -> Result<()> {
+        thread::sleep(self.intervals.next(node.idx));
+        Ok(())
+    }
+}
+
+/// Wait for a file, relative to the node's dir, to exist.
+pub(crate) struct FileAppears {
+    pub(crate) file: PathBuf,
+    pub(crate) timeout: Duration,
+}
+
+impl ReadinessStrategy for FileAppears {
+    fn wait_until_ready(&mut self, node: &LaunchedNode, node_dir: &Path) -> Result<()> {
+        let path = node_dir.join(&self.file);
+        poll_until(&node.name, self.timeout, || Ok(path.exists()))
+    }
+}
+
+/// Wait for a marker to be logged by the node.
+pub(crate) struct LogMarker {
+    pub(crate) marker: String,
+    pub(crate) timeout: Duration,
+}
+
+impl ReadinessStrategy for LogMarker {
+    fn wait_until_ready(&mut self, node: &LaunchedNode, node_dir: &Path) -> Result<()> {
+        poll_until(&node.name, self.timeout, || {
+            Ok(logs::newest_log_file(node_dir)
+                .and_then(|path| fs::read_to_string(path).ok())
+                .is_some_and(|log| log.contains(&self.marker)))
+        })
+    }
+}
+
+/// Wait for the node's address to be reachable, see [`wait_for_connect`].
+pub(crate) struct Connect {
+    pub(crate) tcp: bool,
+    pub(crate) timeout: Duration,
+}
+
+impl ReadinessStrategy for Connect {
+    fn wait_until_ready(&mut self, node: &LaunchedNode, _node_dir: &Path) -> Result<()> {
+        let addr = node
+            .addr
+            .ok_or_else(|| eyre!("The address of {} to connect to is unknown", node.name))?;
+        wait_for_connect(addr, self.tcp, self.timeout, READINESS_POLL_INTERVAL)
+    }
+}
+
+/// Run a shell command until it succeeds, see [`wait_for_command`]. It's run with SN_NODE_NAME,
+/// SN_NODE_DIR, SN_NODES_DIR and, if known, SN_NODE_ADDR set.
+pub(crate) struct ReadyCommand {
+    pub(crate) command: String,
+    pub(crate) nodes_dir: PathBuf,
+    pub(crate) timeout: Duration,
+}
+
+impl ReadinessStrategy for ReadyCommand {
+    fn wait_until_ready(&mut self, node: &LaunchedNode, node_dir: &Path) -> Result<()> {
+        let mut envs = vec![
+            ("SN_NODE_NAME", OsString::from(&node.name)),
+            ("SN_NODE_DIR", node_dir.into()),
+            ("SN_NODES_DIR", self.nodes_dir.clone().into()),
+        ];
+        if let Some(addr) = node.addr {
+            envs.push(("SN_NODE_ADDR", addr.to_string().into()));
+        }
+        wait_for_command(&self.command, &envs, self.timeout)
+    }
+}
+
+/// Call `ready` until it returns true, failing if it hasn't after `timeout`.
+fn poll_until(
+    node_name: &str,
+    timeout: Duration,
+    mut ready: impl FnMut() -> Result<bool>,
+) -> Result<()> {
+    let started = Instant::now();
+    while !ready()? {
+        if started.elapsed() >= timeout {
+            return Err(eyre!(
+                "{} wasn't ready within {}s",
+                node_name,
+                timeout.as_secs()
+            ));
+        }
+        thread::sleep(READINESS_POLL_INTERVAL);
+    }
+    debug!(
+        "{} is ready after {:.1}s",
+        node_name,
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Run the user-provided shell `command` until it exits successfully, or fail after `timeout`.
+pub(crate) fn wait_for_command(
+    command: &str,
+    envs: &[(&str, OsString)],
+    timeout: Duration,
+) -> Result<()> {
+    let started = Instant::now();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let status = shell(command)
+            .envs(envs.iter().map(|(key, value)| (key, value)))
+            .stdin(Stdio::null())
+            .status()
+            .map_err(|error| eyre!("Failed to run readiness command '{command}': {error}"))?;
+
+        if status.success() {
+            debug!("Readiness command succeeded after {attempts} attempt(s)");
+            return Ok(());
+        }
+        trace!("Readiness command attempt #{attempts} exited with {status}");
+
+        if started.elapsed() >= timeout {
+            return Err(eyre!(
+                "Readiness command '{command}' didn't succeed within {}s ({attempts} attempts, last status: {status})",
+                timeout.as_secs()
+            ));
+        }
+
+        thread::sleep(READY_CMD_RETRY_INTERVAL);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// Wait until the node at `addr` is reachable: accepting TCP connections, or for UDP (QUIC),
+/// having its socket bound, which is all that can be checked without speaking its protocol.
+///
+/// The check is retried every `interval`, failing after `timeout`.
+pub(crate) fn wait_for_connect(
+    addr: SocketAddr,
+    tcp: bool,
+    timeout: Duration,
+    interval: Duration,
+) -> Result<()> {
+    let addr = connectable(addr);
+    let started = Instant::now();
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let result = if tcp {
+            TcpStream::connect_timeout(&addr, interval).map(drop)
+        } else {
+            udp_bound(addr)
+        };
+
+        match result {
+            Ok(()) => {
+                debug!("{addr} is reachable after {attempts} attempt(s)");
+                return Ok(());
+            }
+            Err(error) if started.elapsed() >= timeout => {
+                return Err(eyre!(
+                    "{addr} wasn't reachable within {}s ({attempts} attempts, last error: {error})",
+                    timeout.as_secs()
+                ));
+            }
+            Err(error) => trace!("Connect probe attempt #{attempts} to {addr} failed: {error}"),
+        }
+
+        thread::sleep(interval);
+    }
+}
+
+/// The address to reach a node listening on `addr`, which may be the unspecified address.
+fn connectable(mut addr: SocketAddr) -> SocketAddr {
+    if addr.ip().is_unspecified() {
+        addr.set_ip(match addr {
+            SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+            SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+        });
+    }
+    addr
+}
+
+/// Whether a UDP socket is bound to `addr`, i.e. binding it ourselves fails as it's in use.
+fn udp_bound(addr: SocketAddr) -> io::Result<()> {
+    match UdpSocket::bind(addr) {
+        Err(error) if error.kind() == io::ErrorKind::AddrInUse => Ok(()),
+        Err(error) => Err(error),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no socket is bound to it",
+        )),
+    }
+}
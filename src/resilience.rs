@@ -0,0 +1,95 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{process, readiness, LaunchedNode};
+use eyre::{eyre, Result, WrapErr};
+use std::{thread, time::Duration};
+use tracing::{info, warn};
+
+/// How long each remaining node is given to be reachable once the settle period is over.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time between attempts at reaching a remaining node.
+const PROBE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Kill genesis, giving it `shutdown_timeout` to exit, wait `settle`, then check the rest of the
+/// nodes are still running and reachable, failing with the nodes which aren't.
+///
+/// Genesis is killed outright rather than stopped gracefully, as a crash would.
+pub(crate) fn test_genesis_resilience(
+    nodes: &mut [LaunchedNode],
+    settle: Duration,
+    shutdown_timeout: Duration,
+    tcp: bool,
+) -> Result<()> {
+    let genesis = nodes
+        .iter_mut()
+        .find(|node| node.is_genesis())
+        .ok_or_else(|| eyre!("Genesis wasn't launched by this run, so it can't be killed"))?;
+    info!(
+        "Genesis resilience test: killing {} (PID {})...",
+        genesis.name,
+        genesis.child.id()
+    );
+    process::kill_children(
+        [(genesis.name.as_str(), &mut genesis.child)],
+        shutdown_timeout,
+    )
+    .wrap_err("Could not kill genesis")?;
+
+    info!(
+        "Waiting {}s for the network to settle without genesis...",
+        settle.as_secs()
+    );
+    thread::sleep(settle);
+
+    let mut failed = vec![];
+    let remaining: Vec<_> = nodes.iter_mut().filter(|node| !node.is_genesis()).collect();
+    if remaining.is_empty() {
+        return Err(eyre!(
+            "No nodes besides genesis were launched, so there's no network left to check"
+        ));
+    }
+    let total = remaining.len();
+    for node in remaining {
+        if let Some(status) = node.child.try_wait()? {
+            warn!("{} exited ({}) after genesis was killed", node.name, status);
+            failed.push(node.name.clone());
+            continue;
+        }
+        match node.addr {
+            Some(addr) => {
+                if let Err(error) =
+                    readiness::wait_for_connect(addr, tcp, PROBE_TIMEOUT, PROBE_INTERVAL)
+                {
+                    warn!("{} is no longer reachable: {:#}", node.name, error);
+                    failed.push(node.name.clone());
+                }
+            }
+            None => warn!(
+                "{}'s address is unknown, so only its process was checked",
+                node.name
+            ),
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(eyre!(
+            "Genesis resilience test failed: {} of {} remaining nodes aren't healthy: {}",
+            failed.len(),
+            total,
+            failed.join(", ")
+        ));
+    }
+    info!(
+        "Genesis resilience test passed: all {} remaining nodes are running and reachable",
+        total
+    );
+    Ok(())
+}
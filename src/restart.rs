@@ -0,0 +1,82 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{
+    address,
+    process::{self, Restarts},
+    LaunchedNode,
+};
+use eyre::{Result, WrapErr};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// How long a node is given to shut down gracefully before being killed.
+const NODE_STOP_GRACE: Duration = Duration::from_secs(10);
+
+/// Restart the nodes one at a time, reusing their data dirs, waiting `settle` between nodes.
+///
+/// Each node is stopped with SIGTERM (killed if it doesn't exit in time), optionally switched to
+/// the binary at `node_path`, and relaunched through the usual liveness check, which is recorded in
+/// its dir along with how it exited (see [`Restarts`]). `rejoined` then waits for it to be back,
+/// given the lengths its logs had before it was restarted, before moving on.
+pub(crate) fn rolling_restart<'a>(
+    nodes: &mut [LaunchedNode<'a>],
+    nodes_dir: &Path,
+    node_path: Option<&'a Path>,
+    settle: Duration,
+    genesis_first: bool,
+    mut rejoined: impl FnMut(&mut LaunchedNode<'a>, &BTreeMap<PathBuf, u64>) -> Result<()>,
+) -> Result<()> {
+    let mut order: Vec<_> = (0..nodes.len()).collect();
+    // nodes are in launch order, so genesis (if launched in this run) is the first one
+    if !genesis_first && nodes.first().is_some_and(LaunchedNode::is_genesis) {
+        order.rotate_left(1);
+    }
+
+    let total = order.len();
+    for (step, idx) in order.into_iter().enumerate() {
+        let node = &mut nodes[idx];
+        info!("Restarting {} ({}/{})...", node.name, step + 1, total);
+        let node_dir = nodes_dir.join(&node.name);
+        let logged_before = address::log_lengths(&node_dir);
+        let mut restarts = Restarts::load(&node_dir);
+
+        let status = process::stop_child(&mut node.child, NODE_STOP_GRACE)
+            .wrap_err_with(|| format!("Could not stop {}", node.name))?;
+        info!("{} stopped ({})", node.name, status);
+        if let Err(error) = restarts.record_exit(&node_dir, status) {
+            warn!("{:?}", error);
+        }
+
+        if let Some(node_path) = node_path {
+            node.cmd.set_path(node_path);
+        }
+        node.child = node
+            .cmd
+            .run(&node.name, nodes_dir)
+            .wrap_err_with(|| format!("Could not restart {}", node.name))?;
+        if let Err(error) = restarts.record_restart(&node_dir) {
+            warn!("{:?}", error);
+        }
+        rejoined(node, &logged_before)
+            .wrap_err_with(|| format!("{} didn't rejoin once restarted", node.name))?;
+
+        if step + 1 < total {
+            thread::sleep(settle);
+        }
+    }
+
+    info!("Rolling restart of {} nodes done", total);
+    Ok(())
+}
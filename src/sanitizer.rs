@@ -0,0 +1,76 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Sanitizer an instrumented sn_node binary was built with, for --sanitizer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum Sanitizer {
+    Address,
+    Thread,
+}
+
+impl Sanitizer {
+    /// Env var the sanitizer's runtime reads its options from.
+    pub(crate) fn options_var(self) -> &'static str {
+        match self {
+            Self::Address => "ASAN_OPTIONS",
+            Self::Thread => "TSAN_OPTIONS",
+        }
+    }
+
+    /// Options the nodes are run with unless overridden with --sanitizer-options: report every
+    /// error rather than stopping at the first, and add the context most useful to debug them.
+    pub(crate) fn default_options(self) -> &'static str {
+        match self {
+            Self::Address => "halt_on_error=0:detect_leaks=1:detect_stack_use_after_return=1",
+            Self::Thread => "halt_on_error=0:second_deadlock_stack=1:history_size=7",
+        }
+    }
+
+    /// Prefix of the report files written into node dirs, which the runtime follows with the
+    /// process' PID.
+    fn report_prefix(self) -> &'static str {
+        match self {
+            Self::Address => "asan",
+            Self::Thread => "tsan",
+        }
+    }
+
+    /// Value of the options env var for a node in `node_dir`, writing its reports there.
+    ///
+    /// `log_path` comes first, so it can still be overridden by `options`.
+    pub(crate) fn options(self, node_dir: &Path, options: &str) -> OsString {
+        let mut value = OsString::from("log_path=");
+        value.push(node_dir.join(self.report_prefix()));
+        if !options.is_empty() {
+            value.push(":");
+            value.push(options);
+        }
+        value
+    }
+
+    /// The report files the sanitizer has written into `node_dir`, if any.
+    pub(crate) fn reports(self, node_dir: &Path) -> Vec<PathBuf> {
+        let prefix = format!("{}.", self.report_prefix());
+        let mut reports: Vec<_> = fs::read_dir(node_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+            .map(|entry| entry.path())
+            .collect();
+        reports.sort();
+        reports
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{cmd::Invocation, LaunchedNode};
+use eyre::{Result, WrapErr};
+use std::{env, ffi::OsStr, fmt::Write, fs, path::Path, time::Duration};
+use tracing::info;
+
+/// How the launcher waited for genesis before launching the other nodes.
+pub(crate) enum GenesisWait<'a> {
+    Sleep,
+    ReadyCmd {
+        command: &'a str,
+        contacts: &'a Path,
+    },
+}
+
+/// Write a POSIX shell script which launches `nodes` the same way the launcher did: each node's
+/// command with its final args and env, and the waits in between, taken in order from `intervals`
+/// except for genesis when it was waited for with a command.
+pub(crate) fn write_script(
+    path: &Path,
+    nodes: &[LaunchedNode],
+    nodes_dir: &Path,
+    intervals: &[Duration],
+    genesis_wait: GenesisWait,
+) -> Result<()> {
+    let mut script = "#!/bin/sh\n".to_string();
+    script.push_str(
+        "# Launch recorded by sn_launch_tool. The nodes are run in the background, and keep\n",
+    );
+    script.push_str("# running after this script exits, as with the tool.\n");
+    script.push_str("set -e\n");
+    // the recorded paths may be relative to where the tool was run
+    let current_dir = env::current_dir().wrap_err("Could not read the current directory")?;
+    let _ = writeln!(script, "cd {}", quote(current_dir.as_os_str()));
+
+    let mut intervals = intervals.iter();
+    for (idx, node) in nodes.iter().enumerate() {
+        let invocation = node.cmd.invocation(&node.name, nodes_dir);
+        let _ = writeln!(script, "\n# Step {}: launch {}", idx + 1, node.name);
+        let _ = writeln!(script, "{} &", command_line(&invocation));
+
+        if node.is_genesis() {
+            if let GenesisWait::ReadyCmd { command, contacts } = genesis_wait {
+                let _ = writeln!(
+                    script,
+                    "# wait for genesis to be ready, retrying --genesis-ready-cmd"
+                );
+                let _ = writeln!(
+                    script,
+                    "until SN_GENESIS_CONTACTS={} SN_NODES_DIR={} sh -c {}; do sleep 0.5; done",
+                    quote(contacts.as_os_str()),
+                    quote(nodes_dir.as_os_str()),
+                    quote(OsStr::new(command))
+                );
+                continue;
+            }
+        }
+        if let Some(interval) = intervals.next() {
+            let _ = writeln!(script, "sleep {:.3}", interval.as_secs_f64());
+        }
+    }
+
+    fs::write(path, script)
+        .wrap_err_with(|| format!("Could not write launch script {}", path.display()))?;
+    make_executable(path)?;
+    info!("Launch script written to {}", path.display());
+
+    Ok(())
+}
+
+/// The invocation as a shell command.
+pub(crate) fn command_line(invocation: &Invocation) -> String {
+    let mut envs = String::new();
+    for (key, value) in &invocation.envs {
+        let _ = write!(envs, "{}={} ", key.to_string_lossy(), quote(value));
+    }
+    let mut command = quote(&invocation.program);
+    for arg in &invocation.args {
+        command.push(' ');
+        command.push_str(&quote(arg));
+    }
+
+    match &invocation.current_dir {
+        Some(dir) => format!("(cd {} && {envs}exec {command})", quote(dir.as_os_str())),
+        None => format!("{envs}{command}"),
+    }
+}
+
+/// Single-quote `value` for a POSIX shell.
+fn quote(value: &OsStr) -> String {
+    format!("'{}'", value.to_string_lossy().replace('\'', r"'\''"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .wrap_err_with(|| format!("Could not make {} executable", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{path::PathBuf, process::Command};
+
+    const AWKWARD: &str = r#"it's "50%" of $HOME \ `x`"#;
+
+    #[test]
+    fn values_are_single_quoted() {
+        assert_eq!(quote(OsStr::new("plain")), "'plain'");
+        assert_eq!(quote(OsStr::new("it's")), r"'it'\''s'");
+        assert_eq!(
+            quote(OsStr::new(AWKWARD)),
+            r#"'it'\''s "50%" of $HOME \ `x`'"#
+        );
+    }
+
+    #[test]
+    fn command_lines_quote_the_program_args_env_and_dir() {
+        let invocation = Invocation {
+            program: "/opt/it's/sn_node".into(),
+            args: vec!["--root-dir".into(), "$HOME/nodes".into()],
+            envs: vec![("RUST_LOG".into(), "a b".into())],
+            current_dir: Some(PathBuf::from("/srv/\"test\"net")),
+        };
+        assert_eq!(
+            command_line(&invocation),
+            r#"(cd '/srv/"test"net' && RUST_LOG='a b' exec '/opt/it'\''s/sn_node' '--root-dir' '$HOME/nodes')"#
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_lines_pass_args_and_env_through_the_shell_unchanged() -> Result<()> {
+        let invocation = Invocation {
+            program: "sh".into(),
+            args: vec![
+                "-c".into(),
+                r#"printf '%s|' "$0" "$SN_TEST_VALUE""#.into(),
+                AWKWARD.into(),
+            ],
+            envs: vec![("SN_TEST_VALUE".into(), AWKWARD.into())],
+            current_dir: None,
+        };
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command_line(&invocation))
+            .output()?;
+
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("{AWKWARD}|{AWKWARD}|")
+        );
+        Ok(())
+    }
+}
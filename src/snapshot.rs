@@ -0,0 +1,79 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use eyre::{eyre, Result, WrapErr};
+use std::{fs, path::Path};
+use tracing::{debug, info};
+
+/// Optional file in a snapshot with the `sn_node -V` output of the node which produced it.
+const SNAPSHOT_VERSION_FILE: &str = "sn_node_version";
+
+/// Seed the genesis node's root dir with the data in `snapshot_dir`.
+///
+/// If the snapshot records the version of sn_node it was taken with, it must match `node_version`.
+pub(crate) fn seed_genesis(
+    snapshot_dir: &Path,
+    genesis_dir: &Path,
+    node_version: &str,
+) -> Result<()> {
+    if !snapshot_dir.is_dir() {
+        return Err(eyre!(
+            "Genesis snapshot {} is not a directory",
+            snapshot_dir.display()
+        ));
+    }
+
+    match fs::read_to_string(snapshot_dir.join(SNAPSHOT_VERSION_FILE)) {
+        Ok(snapshot_version) if snapshot_version.trim() != node_version => {
+            return Err(eyre!(
+                "Genesis snapshot {} was taken with '{}', which doesn't match '{}'",
+                snapshot_dir.display(),
+                snapshot_version.trim(),
+                node_version
+            ));
+        }
+        Ok(_) => {}
+        Err(_) => debug!(
+            "Genesis snapshot has no {} file, skipping the version check",
+            SNAPSHOT_VERSION_FILE
+        ),
+    }
+
+    info!(
+        "Seeding genesis data from snapshot {} into {}",
+        snapshot_dir.display(),
+        genesis_dir.display()
+    );
+    copy_dir_all(snapshot_dir, genesis_dir).wrap_err("Could not copy the genesis snapshot")
+}
+
+/// Copy `from` and everything in it to `to`.
+pub(crate) fn copy_dir_all(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)
+        .wrap_err_with(|| format!("Could not create directory {}", to.display()))?;
+    for entry in fs::read_dir(from)
+        .wrap_err_with(|| format!("Could not read directory {}", from.display()))?
+    {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target).wrap_err_with(|| {
+                format!(
+                    "Could not copy {} to {}",
+                    entry.path().display(),
+                    target.display()
+                )
+            })?;
+        }
+    }
+
+    Ok(())
+}
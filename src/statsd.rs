@@ -0,0 +1,81 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::timings::Phases;
+use std::{
+    net::{SocketAddr, UdpSocket},
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+/// Send the metrics of a launch timed by `phases` to the StatsD server at `addr`, with names
+/// starting with `prefix`. `nodes_up` is the number of nodes launched, or `None` if it failed.
+///
+/// Metrics are best effort: failing to send them is only reported.
+pub(crate) fn send_launch_metrics(
+    addr: SocketAddr,
+    prefix: &str,
+    phases: &Phases,
+    nodes_up: Option<usize>,
+) {
+    let mut metrics = vec![timer(prefix, "launch", phases.total())];
+    for (name, duration) in phases.phases() {
+        metrics.push(timer(
+            prefix,
+            &format!("phase.{}", name.replace(' ', "_")),
+            *duration,
+        ));
+    }
+    for duration in phases.node_launches() {
+        metrics.push(timer(prefix, "node_launch", *duration));
+    }
+    metrics.push(format!(
+        "{prefix}.spawn_retries:{}|c",
+        phases.spawn_retries()
+    ));
+    match nodes_up {
+        Some(nodes_up) => {
+            metrics.push(format!("{prefix}.nodes_up:{nodes_up}|g"));
+            metrics.push(format!("{prefix}.launches:1|c"));
+        }
+        None => metrics.push(format!("{prefix}.launches_failed:1|c")),
+    }
+
+    let bind_addr = if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let socket = match UdpSocket::bind(bind_addr) {
+        Ok(socket) => socket,
+        Err(error) => {
+            warn!("Could not send the launch metrics to StatsD: {}", error);
+            return;
+        }
+    };
+    // one metric per datagram, so none of them gets too big to be delivered
+    for metric in &metrics {
+        if let Err(error) = socket.send_to(metric.as_bytes(), addr) {
+            warn!(
+                "Could not send the launch metrics to StatsD at {}: {}",
+                addr, error
+            );
+            return;
+        }
+    }
+    debug!(
+        "Sent {} launch metrics to StatsD at {}",
+        metrics.len(),
+        addr
+    );
+}
+
+fn timer(prefix: &str, name: &str, duration: Duration) -> String {
+    format!("{}.{}:{}|ms", prefix, name, duration.as_millis())
+}
@@ -0,0 +1,182 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::cmd::{Invocation, NodeCmd};
+use eyre::{Result, WrapErr};
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Name of the target grouping the nodes' units.
+pub(crate) const TARGET: &str = "sn-testnet.target";
+
+/// Seconds systemd waits before restarting a node which failed.
+const RESTART_SEC: u32 = 5;
+
+/// Write a systemd service unit for each of `nodes`, named after the node, e.g.
+/// `sn-node-genesis.service`, plus the [`TARGET`] grouping them, to `dir`.
+///
+/// Each unit runs the node's command with its final args and env from the current directory,
+/// restarting it on failure. The other nodes are ordered after genesis, as they need its contacts.
+pub(crate) fn write_units(dir: &Path, nodes: &[(String, NodeCmd)], nodes_dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .wrap_err_with(|| format!("Could not create directory {}", dir.display()))?;
+    // the nodes' paths may be relative to where the tool was run
+    let current_dir = env::current_dir().wrap_err("Could not read the current directory")?;
+
+    let genesis_unit = nodes.first().map(|(name, _)| unit_name(name));
+    let mut unit_names = vec![];
+    for (name, cmd) in nodes {
+        let unit_name = unit_name(name);
+        let invocation = cmd.invocation(name, nodes_dir);
+        let working_dir = match &invocation.current_dir {
+            Some(dir) => current_dir.join(dir),
+            None => current_dir.clone(),
+        };
+
+        let mut unit = "# Generated by sn_launch_tool\n[Unit]\n".to_string();
+        let _ = writeln!(unit, "Description=Safe node {name}");
+        let _ = writeln!(unit, "PartOf={TARGET}");
+        if let Some(genesis_unit) = genesis_unit.as_ref().filter(|unit| **unit != unit_name) {
+            let _ = writeln!(unit, "After={genesis_unit}");
+            let _ = writeln!(unit, "Wants={genesis_unit}");
+        }
+        let _ = writeln!(unit, "\n[Service]");
+        let _ = writeln!(unit, "Type=simple");
+        // unlike command lines, paths aren't unquoted
+        let _ = writeln!(
+            unit,
+            "WorkingDirectory={}",
+            working_dir.to_string_lossy().replace('%', "%%")
+        );
+        for (key, value) in &invocation.envs {
+            let _ = writeln!(
+                unit,
+                "Environment={}",
+                quote(&format!(
+                    "{}={}",
+                    key.to_string_lossy(),
+                    value.to_string_lossy()
+                ))
+            );
+        }
+        let _ = writeln!(unit, "ExecStart={}", exec_start(&invocation, &current_dir));
+        let _ = writeln!(unit, "Restart=on-failure");
+        let _ = writeln!(unit, "RestartSec={RESTART_SEC}");
+        let _ = writeln!(unit, "\n[Install]");
+        let _ = writeln!(unit, "WantedBy={TARGET}");
+
+        write_unit(dir, &unit_name, unit)?;
+        unit_names.push(unit_name);
+    }
+
+    let mut target = "# Generated by sn_launch_tool\n[Unit]\n".to_string();
+    let _ = writeln!(target, "Description=Safe testnet of {} nodes", nodes.len());
+    let _ = writeln!(target, "Wants={}", unit_names.join(" "));
+    let _ = writeln!(target, "\n[Install]");
+    let _ = writeln!(target, "WantedBy=multi-user.target");
+    write_unit(dir, TARGET, target)?;
+
+    info!(
+        "systemd units for {} nodes and {} written to {}",
+        nodes.len(),
+        TARGET,
+        dir.display()
+    );
+    Ok(())
+}
+
+fn unit_name(node_name: &str) -> String {
+    format!("{node_name}.service")
+}
+
+fn write_unit(dir: &Path, name: &str, unit: String) -> Result<()> {
+    let path = dir.join(name);
+    fs::write(&path, unit)
+        .wrap_err_with(|| format!("Could not write systemd unit {}", path.display()))
+}
+
+/// The invocation as an `ExecStart` command line, with the program made absolute if it's a
+/// relative path, as systemd requires.
+fn exec_start(invocation: &Invocation, current_dir: &Path) -> String {
+    let program = PathBuf::from(&invocation.program);
+    let program = if program.is_relative() && program.components().count() > 1 {
+        current_dir.join(program)
+    } else {
+        program
+    };
+
+    let mut command = quote(&program.to_string_lossy());
+    for arg in &invocation.args {
+        command.push(' ');
+        command.push_str(&quote(&arg.to_string_lossy()));
+    }
+    command
+}
+
+/// Double-quote `value` for a unit file, escaping systemd's specifiers and variables.
+fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%")
+        .replace('$', "$$");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_quoted_with_specifiers_and_variables_escaped() {
+        assert_eq!(quote("plain"), r#""plain""#);
+        assert_eq!(quote("100%"), r#""100%%""#);
+        assert_eq!(quote("$HOME"), r#""$$HOME""#);
+        assert_eq!(quote(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(quote(r"C:\nodes"), r#""C:\\nodes""#);
+        // single quotes mean nothing inside double quotes
+        assert_eq!(quote("it's"), r#""it's""#);
+        assert_eq!(quote(r#"%i "$x" \ '"#), r#""%%i \"$$x\" \\ '""#);
+    }
+
+    // joined with `\` on Windows, where there's no systemd anyway
+    #[cfg(unix)]
+    #[test]
+    fn relative_programs_are_made_absolute() {
+        let invocation = Invocation {
+            program: "./bin/sn_node".into(),
+            args: vec!["--root-dir".into(), "nodes/sn-node-2 (50%)".into()],
+            envs: vec![],
+            current_dir: None,
+        };
+        assert_eq!(
+            exec_start(&invocation, Path::new("/srv/testnet")),
+            r#""/srv/testnet/./bin/sn_node" "--root-dir" "nodes/sn-node-2 (50%%)""#
+        );
+    }
+
+    #[test]
+    fn programs_on_the_path_are_left_as_they_are() {
+        let invocation = Invocation {
+            program: "sn_node".into(),
+            args: vec![],
+            envs: vec![],
+            current_dir: None,
+        };
+        assert_eq!(
+            exec_start(&invocation, Path::new("/srv/testnet")),
+            r#""sn_node""#
+        );
+    }
+}
@@ -0,0 +1,125 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::cmd::NodeCmd;
+use eyre::{Result, WrapErr};
+use std::{
+    fs, io,
+    path::Path,
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, info};
+
+/// File in the nodes dir recording the id of the launch its nodes belong to.
+const LAUNCH_ID_FILE: &str = "launch_id";
+
+/// Env var each node is launched with its tag in.
+const NODE_TAG_ENV: &str = "SN_NODE_TAG";
+
+/// sn_node flag the tag is also passed as, when the binary supports it.
+const NODE_TAG_FLAG: &str = "--node-tag";
+
+/// Stable tags identifying each node of a launch, made of the launch's id and the node's index.
+pub(crate) struct NodeTags {
+    launch_id: String,
+    pass_flag: bool,
+}
+
+impl NodeTags {
+    /// Tags for the nodes in `nodes_dir`, reusing the launch id recorded there when adding nodes to
+    /// an existing network, or recording a new one otherwise, unless it's a `dry_run`.
+    pub(crate) fn load(
+        nodes_dir: &Path,
+        add_nodes: bool,
+        dry_run: bool,
+        node_cmd: &NodeCmd,
+    ) -> Result<Self> {
+        let path = nodes_dir.join(LAUNCH_ID_FILE);
+        let existing = if add_nodes {
+            match fs::read_to_string(&path) {
+                Ok(launch_id) => Some(launch_id.trim().to_string()),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+                Err(error) => {
+                    return Err(error)
+                        .wrap_err_with(|| format!("Could not read launch id {}", path.display()))
+                }
+            }
+        } else {
+            None
+        };
+
+        let launch_id = match existing {
+            Some(launch_id) => launch_id,
+            None if dry_run => new_launch_id(),
+            None => {
+                let launch_id = new_launch_id();
+                fs::create_dir_all(nodes_dir).wrap_err_with(|| {
+                    format!("Could not create directory {}", nodes_dir.display())
+                })?;
+                fs::write(&path, &launch_id)
+                    .wrap_err_with(|| format!("Could not write launch id {}", path.display()))?;
+                launch_id
+            }
+        };
+        info!("Tagging the nodes of launch {}", launch_id);
+
+        let pass_flag = node_cmd.supports_flag(NODE_TAG_FLAG)?;
+        if !pass_flag {
+            debug!(
+                "sn_node doesn't support {}, the tags are only passed as {}",
+                NODE_TAG_FLAG, NODE_TAG_ENV
+            );
+        }
+
+        Ok(Self {
+            launch_id,
+            pass_flag,
+        })
+    }
+
+    /// The tag of node `idx`.
+    pub(crate) fn tag(&self, idx: usize) -> String {
+        format!("{}-{}", self.launch_id, idx)
+    }
+
+    /// `node_cmd` with the tag of node `idx` added to its env, and args if supported.
+    fn apply<'a>(&self, node_cmd: &NodeCmd<'a>, idx: usize) -> NodeCmd<'a> {
+        let tag = self.tag(idx);
+        info!("Node #{} is tagged {}", idx, tag);
+
+        let mut cmd = node_cmd.clone();
+        cmd.push_env(NODE_TAG_ENV, tag.clone());
+        if self.pass_flag {
+            cmd.push_arg(NODE_TAG_FLAG);
+            cmd.push_arg(tag);
+        }
+        cmd
+    }
+}
+
+/// `node_cmd` with the tag of node `idx` if the nodes are tagged.
+pub(crate) fn with_node_tag<'a>(
+    node_cmd: &NodeCmd<'a>,
+    node_tags: Option<&NodeTags>,
+    idx: usize,
+) -> NodeCmd<'a> {
+    match node_tags {
+        Some(node_tags) => node_tags.apply(node_cmd, idx),
+        None => node_cmd.clone(),
+    }
+}
+
+/// A short id, unique enough to tell launches apart.
+fn new_launch_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_nanos() as u32);
+    format!("{:08x}", nanos ^ process::id().rotate_left(16))
+}
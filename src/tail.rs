@@ -0,0 +1,136 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::logs;
+use regex::Regex;
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+/// How often each node's log is checked for new lines, and the merged lines are printed.
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Minimum level of the log lines to show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+pub(crate) enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    fn parse(level: &str) -> Option<Self> {
+        match level {
+            "TRACE" => Some(Self::Trace),
+            "DEBUG" => Some(Self::Debug),
+            "INFO" => Some(Self::Info),
+            "WARN" => Some(Self::Warn),
+            "ERROR" => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Follow the newest log file of each of the `nodes`, named and in their dir, printing their new
+/// lines prefixed with the node's name, and roughly ordered by their timestamps. Lines below
+/// `min_level` are skipped. Runs until interrupted.
+pub(crate) fn follow_all(nodes: Vec<(String, PathBuf)>, min_level: Option<LogLevel>) {
+    let (lines_tx, lines_rx) = mpsc::channel();
+    for (name, node_dir) in nodes {
+        let lines_tx = lines_tx.clone();
+        let _ = thread::spawn(move || follow(&name, &node_dir, &lines_tx));
+    }
+    drop(lines_tx);
+
+    let timestamp = Regex::new(r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?")
+        .expect("valid timestamp regex");
+    let level = Regex::new(r"\b(TRACE|DEBUG|INFO|WARN|ERROR)\b").expect("valid level regex");
+
+    // lines are printed in batches, each sorted by timestamp, to merge the logs roughly in order
+    let mut batch = vec![];
+    loop {
+        thread::sleep(FOLLOW_POLL_INTERVAL);
+        batch.extend(lines_rx.try_iter());
+        if batch.is_empty() {
+            continue;
+        }
+
+        let mut last_time = String::new();
+        let mut keyed: Vec<_> = batch
+            .drain(..)
+            .map(|(name, line): (String, String)| {
+                // lines without a timestamp, e.g. continued from the previous one, follow it
+                if let Some(time) = timestamp.find(&line) {
+                    last_time = time.as_str().replace(' ', "T");
+                }
+                (last_time.clone(), name, line)
+            })
+            .collect();
+        keyed.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+        for (_, name, line) in keyed {
+            let line_level = level
+                .captures(&line)
+                .and_then(|captures| LogLevel::parse(&captures[1]));
+            if let (Some(min_level), Some(line_level)) = (min_level, line_level) {
+                if line_level < min_level {
+                    continue;
+                }
+            }
+            println!("{name} | {line}");
+        }
+    }
+}
+
+/// Send each new line of the node's newest log file, following it as it's rotated.
+fn follow(name: &str, node_dir: &Path, lines_tx: &mpsc::Sender<(String, String)>) {
+    // start from the end of the current log, like `tail -f`
+    let mut current = logs::newest_log_file(node_dir).and_then(|path| {
+        let mut file = File::open(&path).ok()?;
+        file.seek(SeekFrom::End(0)).ok()?;
+        Some((path, file))
+    });
+    let mut partial = String::new();
+
+    loop {
+        if let Some((_, file)) = &mut current {
+            let mut bytes = vec![];
+            if file.read_to_end(&mut bytes).is_ok() {
+                partial.push_str(&String::from_utf8_lossy(&bytes));
+                while let Some((line, rest)) = partial.split_once('\n') {
+                    let line = line.trim_end_matches('\r').to_string();
+                    partial = rest.to_string();
+                    if lines_tx.send((name.to_string(), line)).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+
+        // switch to the next log file once rotated, having read the rest of the previous one
+        if let Some(newest) = logs::newest_log_file(node_dir) {
+            if current.as_ref().map(|(path, _)| path) != Some(&newest) {
+                if let Ok(file) = File::open(&newest) {
+                    current = Some((newest, file));
+                    partial.clear();
+                    continue;
+                }
+            }
+        }
+
+        thread::sleep(FOLLOW_POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use std::{
+    fs,
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use tracing::info;
+
+/// How often a settling file is checked for changes.
+const QUIET_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long each phase of a launch took.
+pub(crate) struct Phases {
+    started: Instant,
+    phases: Vec<(&'static str, Duration)>,
+    node_launches: Vec<Duration>,
+    spawn_retries: Arc<AtomicU32>,
+}
+
+impl Phases {
+    pub(crate) fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            phases: vec![],
+            node_launches: vec![],
+            spawn_retries: Default::default(),
+        }
+    }
+
+    /// Run `phase`, recording how long it took under `name`.
+    pub(crate) fn time<T>(&mut self, name: &'static str, phase: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = phase();
+        self.record(name, started.elapsed());
+        result
+    }
+
+    pub(crate) fn record(&mut self, name: &'static str, duration: Duration) {
+        self.phases.push((name, duration));
+    }
+
+    /// Record how long launching one of the joining nodes took, excluding the interval after it.
+    pub(crate) fn record_node_launch(&mut self, duration: Duration) {
+        self.node_launches.push(duration);
+    }
+
+    /// Time since the launch started.
+    pub(crate) fn total(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// The phases recorded so far, in order.
+    pub(crate) fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// The launch durations recorded so far of the joining nodes.
+    pub(crate) fn node_launches(&self) -> &[Duration] {
+        &self.node_launches
+    }
+
+    /// Counter for the node command to count its spawn retries in, see
+    /// [`NodeCmd::set_retry_counter`](crate::cmd::NodeCmd::set_retry_counter).
+    pub(crate) fn retry_counter(&self) -> Arc<AtomicU32> {
+        self.spawn_retries.clone()
+    }
+
+    /// Number of times nodes were respawned after failing to start.
+    pub(crate) fn spawn_retries(&self) -> u32 {
+        self.spawn_retries.load(Ordering::Relaxed)
+    }
+
+    /// Log the total launch time broken down into its phases.
+    pub(crate) fn log_summary(&self) {
+        let total = self.started.elapsed();
+        info!("Launch took {:.1}s:", total.as_secs_f64());
+        for (name, duration) in &self.phases {
+            info!(
+                "  {}: {:.1}s ({:.0}%)",
+                name,
+                duration.as_secs_f64(),
+                100.0 * duration.as_secs_f64() / total.as_secs_f64().max(f64::EPSILON)
+            );
+        }
+
+        if let (Some(min), Some(max)) = (
+            self.node_launches.iter().min(),
+            self.node_launches.iter().max(),
+        ) {
+            let sum: Duration = self.node_launches.iter().sum();
+            info!(
+                "  per joining node: min {:.1}s, avg {:.1}s, max {:.1}s",
+                min.as_secs_f64(),
+                sum.as_secs_f64() / self.node_launches.len() as f64,
+                max.as_secs_f64()
+            );
+        }
+        let spawn_retries = self.spawn_retries();
+        if spawn_retries > 0 {
+            info!(
+                "  nodes respawned after failing to start: {}",
+                spawn_retries
+            );
+        }
+    }
+}
+
+/// How long after `since` the file at `path` was last written, if it exists.
+pub(crate) fn written_after(path: &Path, since: SystemTime) -> Option<Duration> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    modified.duration_since(since).ok()
+}
+
+/// Wait until the file at `path` hasn't changed size or modification time for `quiet`, giving up
+/// after `timeout`. Returns whether it settled.
+pub(crate) fn wait_until_quiet(path: &Path, quiet: Duration, timeout: Duration) -> bool {
+    let state = || {
+        fs::metadata(path)
+            .ok()
+            .map(|metadata| (metadata.len(), metadata.modified().ok()))
+    };
+
+    let started = Instant::now();
+    let mut last_state = state();
+    let mut last_change = started;
+    loop {
+        if last_change.elapsed() >= quiet {
+            return true;
+        }
+        if started.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(QUIET_POLL_INTERVAL);
+        let current = state();
+        if current != last_state {
+            last_state = current;
+            last_change = Instant::now();
+        }
+    }
+}
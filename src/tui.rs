@@ -0,0 +1,356 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{address, exits, logs, process::Restarts, LaunchedNode};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use eyre::Result;
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    time::{Duration, Instant},
+};
+
+/// How often the dashboard is refreshed when no key is pressed.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often a node exiting is checked for between refreshes, when exits are signalled (Unix), so
+/// it's shown without waiting for the next refresh.
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How many bytes from the end of the newest log file are read to show the recent log lines.
+const LOG_TAIL_BYTES: u64 = 16 * 1024;
+
+/// Number of recent log lines shown for the selected node.
+const LOG_TAIL_LINES: usize = 20;
+
+/// How often the disk usage of each node's dir, which is walked to add it up, is refreshed.
+const DISK_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+enum Status {
+    /// Restarted, and yet to log its address again, given the lengths its logs had before.
+    Starting(BTreeMap<PathBuf, u64>),
+    Up,
+    Dead,
+}
+
+struct NodeView<'a> {
+    node: LaunchedNode<'a>,
+    dir: PathBuf,
+    started: Instant,
+    status: Status,
+    restarts: Restarts,
+    /// Size of the node's dir, and when it was added up.
+    disk: Option<(u64, Instant)>,
+}
+
+/// Show a live dashboard of the launched nodes until the user quits.
+///
+/// Quitting the dashboard leaves the nodes which are still up running.
+pub(crate) fn run(nodes_dir: &Path, nodes: Vec<LaunchedNode>) -> Result<()> {
+    let mut nodes: Vec<_> = nodes
+        .into_iter()
+        .map(|node| {
+            let dir = nodes_dir.join(&node.name);
+            NodeView {
+                restarts: Restarts::load(&dir),
+                dir,
+                node,
+                started: Instant::now(),
+                status: Status::Up,
+                disk: None,
+            }
+        })
+        .collect();
+
+    exits::watch()?;
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, nodes_dir, &mut nodes);
+    ratatui::restore();
+
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    nodes_dir: &Path,
+    nodes: &mut [NodeView],
+) -> Result<()> {
+    let mut table_state = TableState::default().with_selected(Some(0));
+    let mut message = String::new();
+    // the node to kill once confirmed
+    let mut confirming_kill = None;
+
+    loop {
+        for node in nodes.iter_mut() {
+            refresh(node)?;
+        }
+
+        terminal.draw(|frame| draw(frame, nodes, &mut table_state, &message))?;
+        let drawn = Instant::now();
+
+        // wait for a key, a node exiting or the next refresh, whichever comes first
+        let event = loop {
+            if event::poll(EXIT_CHECK_INTERVAL)? {
+                break Some(event::read()?);
+            }
+            if exits::take_exited() || drawn.elapsed() >= REFRESH_INTERVAL {
+                break None;
+            }
+        };
+        let key = match event {
+            Some(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        if let Some(idx) = confirming_kill.take() {
+            message = match (key.code, nodes.get_mut(idx)) {
+                (KeyCode::Char('y'), Some(node)) => kill(node),
+                _ => "Kill cancelled".to_string(),
+            };
+            continue;
+        }
+
+        let selected = table_state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => {
+                table_state.select(Some((selected + 1).min(nodes.len().saturating_sub(1))));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                table_state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Char('x') | KeyCode::Char('K') => {
+                if let Some(node) = nodes.get(selected) {
+                    message = format!("Kill {}? (y/n)", node.node.name);
+                    confirming_kill = Some(selected);
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(node) = nodes.get_mut(selected) {
+                    message = restart(node, nodes_dir);
+                    // the restarted node writes to the same terminal, so make sure it's repainted
+                    terminal.clear()?;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Update the node's status, and its disk usage if it's due.
+fn refresh(node: &mut NodeView) -> Result<()> {
+    if let Status::Starting(_) | Status::Up = node.status {
+        if let Some(exit_status) = node.node.child.try_wait()? {
+            node.status = Status::Dead;
+            record_exit(node, exit_status);
+        }
+    }
+    if let Status::Starting(logged_before) = &node.status {
+        if let Some(addr) =
+            address::discover_node_addr_since(&node.dir, logged_before, Duration::ZERO)?
+        {
+            node.node.addr = Some(addr);
+            node.status = Status::Up;
+        }
+    }
+
+    let disk_due = match node.disk {
+        Some((_, checked)) => checked.elapsed() >= DISK_REFRESH_INTERVAL,
+        None => true,
+    };
+    if disk_due {
+        node.disk = Some((dir_size(&node.dir), Instant::now()));
+    }
+
+    Ok(())
+}
+
+fn kill(node: &mut NodeView) -> String {
+    if let Status::Dead = node.status {
+        return format!("{} is not running", node.node.name);
+    }
+
+    match node.node.child.kill().and_then(|()| node.node.child.wait()) {
+        Ok(exit_status) => {
+            node.status = Status::Dead;
+            record_exit(node, exit_status);
+            format!("Killed {}", node.node.name)
+        }
+        Err(error) => format!("Failed to kill {}: {error}", node.node.name),
+    }
+}
+
+fn restart(node: &mut NodeView, nodes_dir: &Path) -> String {
+    let logged_before = address::log_lengths(&node.dir);
+    let logged_addr = matches!(
+        address::discover_node_addr(&node.dir, Duration::ZERO),
+        Ok(Some(_))
+    );
+    if !matches!(node.status, Status::Dead) {
+        let message = kill(node);
+        if !matches!(node.status, Status::Dead) {
+            return message;
+        }
+    }
+
+    match node.node.cmd.run(&node.node.name, nodes_dir) {
+        Ok(child) => {
+            node.node.child = child;
+            node.started = Instant::now();
+            // a node which never logged its address can't be told to be up again by doing so
+            node.status = if logged_addr {
+                Status::Starting(logged_before)
+            } else {
+                Status::Up
+            };
+            if let Err(error) = node.restarts.record_restart(&node.dir) {
+                return format!("Restarted {}, but {error}", node.node.name);
+            }
+            format!("Restarted {}", node.node.name)
+        }
+        Err(error) => {
+            node.status = Status::Dead;
+            format!("Failed to restart {}: {error}", node.node.name)
+        }
+    }
+}
+
+/// Record in the node's dir how it exited, which the dashboard has nowhere to report failing to.
+fn record_exit(node: &mut NodeView, exit_status: ExitStatus) {
+    let _ = node.restarts.record_exit(&node.dir, exit_status);
+}
+
+fn draw(frame: &mut Frame, nodes: &[NodeView], table_state: &mut TableState, message: &str) {
+    let [table_area, logs_area, help_area] = Layout::vertical([
+        Constraint::Length(nodes.len() as u16 + 3),
+        Constraint::Min(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let rows = nodes.iter().map(|node| {
+        let (status, style) = match node.status {
+            Status::Starting(_) => ("starting".to_string(), Style::default().fg(Color::Yellow)),
+            Status::Up => ("up".to_string(), Style::default().fg(Color::Green)),
+            Status::Dead => ("dead".to_string(), Style::default().fg(Color::Red)),
+        };
+        let uptime = match node.status {
+            Status::Starting(_) | Status::Up => format_duration(node.started.elapsed()),
+            Status::Dead => "-".to_string(),
+        };
+
+        Row::new(vec![
+            node.node.name.clone(),
+            node.node.child.id().to_string(),
+            status,
+            uptime,
+            node.restarts.count.to_string(),
+            node.restarts
+                .last_exit
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+            node.disk
+                .map_or_else(|| "-".to_string(), |(size, _)| format_size(size)),
+        ])
+        .style(style)
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(20),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(12),
+            Constraint::Length(10),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            "Node",
+            "PID",
+            "Status",
+            "Uptime",
+            "Restarts",
+            "Last exit",
+            "Disk",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .block(Block::default().borders(Borders::ALL).title("Nodes"));
+    frame.render_stateful_widget(table, table_area, table_state);
+
+    let selected = table_state.selected().and_then(|idx| nodes.get(idx));
+    let (title, lines) = match selected {
+        Some(node) => (
+            format!("Recent logs of {}", node.node.name),
+            recent_log_lines(&node.dir),
+        ),
+        None => ("Recent logs".to_string(), vec![]),
+    };
+    let logs = Paragraph::new(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(logs, logs_area);
+
+    let help = if message.is_empty() {
+        "↑/↓ or j/k: select  x: kill  r: restart  q: quit".to_string()
+    } else {
+        message.to_string()
+    };
+    frame.render_widget(Paragraph::new(help), help_area);
+}
+
+/// Last lines of the most recently modified log file in the node's dir.
+fn recent_log_lines(node_dir: &Path) -> Vec<String> {
+    let tail = logs::tail(node_dir, LOG_TAIL_BYTES);
+    let lines: Vec<_> = tail.lines().map(str::to_string).collect();
+    lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].to_vec()
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| match entry.metadata() {
+                    Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+                    Ok(metadata) => metadata.len(),
+                    Err(_) => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
@@ -0,0 +1,143 @@
+// Copyright 2023 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{address, readiness, LaunchedNode};
+use eyre::{eyre, Result};
+use std::{path::Path, process::Stdio, thread, time::Duration};
+use tracing::{info, warn};
+
+/// How long each node is given to be reachable by the `reachable` check.
+const REACHABLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time between attempts at reaching a node.
+const REACHABLE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A check of the network once it's launched and settled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum Check {
+    /// The launched nodes' processes are all still running.
+    Alive,
+    /// No two launched nodes were discovered on the same address.
+    DistinctAddrs,
+    /// Each launched node's address is reachable.
+    Reachable,
+    /// --verify-cmd succeeds.
+    Smoke,
+}
+
+/// How to verify the network once launched.
+pub(crate) struct Verification<'a> {
+    pub(crate) checks: &'a [Check],
+    pub(crate) settle: Duration,
+    pub(crate) tcp: bool,
+    pub(crate) smoke_cmd: Option<&'a str>,
+    pub(crate) nodes_dir: &'a Path,
+    pub(crate) genesis_contacts: &'a Path,
+}
+
+impl Verification<'_> {
+    /// Wait for the network to settle, then run each of the checks, logging whether each passed,
+    /// and fail if any didn't.
+    pub(crate) fn run(&self, nodes: &mut [LaunchedNode]) -> Result<()> {
+        info!(
+            "Waiting {}s for the network to settle before verifying it...",
+            self.settle.as_secs()
+        );
+        thread::sleep(self.settle);
+
+        let mut failed = vec![];
+        for check in self.checks {
+            let name = clap::ValueEnum::to_possible_value(check).map_or_else(
+                || format!("{check:?}"),
+                |value| value.get_name().to_string(),
+            );
+            match self.check(*check, nodes) {
+                Ok(detail) => info!("PASS {}: {}", name, detail),
+                Err(error) => {
+                    warn!("FAIL {}: {:#}", name, error);
+                    failed.push(name);
+                }
+            }
+        }
+
+        if !failed.is_empty() {
+            return Err(eyre!(
+                "Verification failed: {} of {} checks failed ({})",
+                failed.len(),
+                self.checks.len(),
+                failed.join(", ")
+            ));
+        }
+        info!("Verification passed: all {} checks", self.checks.len());
+        Ok(())
+    }
+
+    /// Run `check`, returning what it found when it passes.
+    fn check(&self, check: Check, nodes: &mut [LaunchedNode]) -> Result<String> {
+        match check {
+            Check::Alive => {
+                let mut dead = vec![];
+                for node in nodes.iter_mut() {
+                    if let Some(status) = node.child.try_wait()? {
+                        dead.push(format!("{} ({})", node.name, status));
+                    }
+                }
+                if !dead.is_empty() {
+                    return Err(eyre!("nodes exited: {}", dead.join(", ")));
+                }
+                Ok(format!("all {} nodes running", nodes.len()))
+            }
+            Check::DistinctAddrs => {
+                let addrs: Vec<_> = nodes
+                    .iter()
+                    .filter_map(|node| Some((node.name.as_str(), node.addr?)))
+                    .collect();
+                let discovered = addrs.len();
+                address::ensure_distinct(addrs)?;
+                Ok(format!("{discovered} discovered addresses are distinct"))
+            }
+            Check::Reachable => {
+                let mut unreachable = vec![];
+                for node in nodes.iter() {
+                    let result = match node.addr {
+                        Some(addr) => readiness::wait_for_connect(
+                            addr,
+                            self.tcp,
+                            REACHABLE_TIMEOUT,
+                            REACHABLE_INTERVAL,
+                        ),
+                        None => Err(eyre!("address unknown")),
+                    };
+                    if let Err(error) = result {
+                        unreachable.push(format!("{} ({:#})", node.name, error));
+                    }
+                }
+                if !unreachable.is_empty() {
+                    return Err(eyre!("nodes unreachable: {}", unreachable.join(", ")));
+                }
+                Ok(format!("all {} nodes reachable", nodes.len()))
+            }
+            Check::Smoke => {
+                let command = self
+                    .smoke_cmd
+                    .ok_or_else(|| eyre!("no --verify-cmd to run"))?;
+                let status = readiness::shell(command)
+                    .env("SN_GENESIS_CONTACTS", self.genesis_contacts)
+                    .env("SN_NODES_DIR", self.nodes_dir)
+                    .stdin(Stdio::null())
+                    .status()
+                    .map_err(|error| eyre!("failed to run '{command}': {error}"))?;
+                if !status.success() {
+                    return Err(eyre!("'{}' exited with {}", command, status));
+                }
+                Ok(format!("'{command}' succeeded"))
+            }
+        }
+    }
+}